@@ -0,0 +1,292 @@
+//! # A registry of multisig wallets
+//!
+//! Contracts on Concordium cannot initialize other contracts, so wallets are
+//! still created with `init_multisig_wallet` from this module. The
+//! `multisig_factory` contract is the one place where they are then
+//! registered, and where the wallets of each owner can be looked up. A
+//! contract counts as a wallet if it answers `view_owners` with a set of
+//! owners; the contract host gives no way to check its module. The owners are indexed
+//! when a wallet is registered, and again when `refresh_wallet` is called
+//! after the owners changed.
+use concordium_std::collections::*;
+use concordium_std::*;
+
+/// Maximum number of wallets returned by `view_wallets` at once.
+pub const MAX_WALLET_PAGE_SIZE: u32 = 100;
+
+/// Position of a wallet in the registry, counting from 0.
+pub type WalletIndex = u64;
+
+#[derive(Serial, DeserialWithState)]
+#[concordium(state_parameter = "S")]
+pub struct FactoryState<S> {
+    /// Registered wallets, in the order they were registered.
+    pub wallets: StateMap<WalletIndex, ContractAddress, S>,
+    pub wallet_count: u64,
+    /// Index of each registered wallet, to reject duplicates.
+    pub wallet_indices: StateMap<ContractAddress, WalletIndex, S>,
+    /// The owners of each wallet, as of the last time they were indexed.
+    pub wallet_owners: StateMap<WalletIndex, BTreeSet<Address>, S>,
+    /// The wallets of each owner, as of the last time they were indexed.
+    pub wallets_by_owner: StateMap<Address, BTreeSet<WalletIndex>, S>,
+}
+
+#[derive(Serialize, SchemaType)]
+pub struct ViewWalletsParams {
+    /// How many wallets to skip.
+    pub skip: u64,
+    /// How many wallets to return, at most `MAX_WALLET_PAGE_SIZE`.
+    pub limit: u32,
+}
+
+#[derive(Serialize, SchemaType)]
+pub struct ViewWalletsResponse {
+    /// Number of registered wallets in total.
+    pub total: u64,
+    pub wallets: Vec<ContractAddress>,
+}
+
+#[derive(Debug, Serialize, SchemaType)]
+pub struct RegisterEvent {
+    pub index: WalletIndex,
+    pub wallet: ContractAddress,
+    pub owners: BTreeSet<Address>,
+}
+
+#[derive(Debug, Serialize, SchemaType)]
+#[concordium(repr(u8))]
+pub enum FactoryEvent {
+    /// A wallet was registered.
+    #[concordium(tag = 0)]
+    Registered(RegisterEvent),
+    /// The owners of a registered wallet were indexed again.
+    #[concordium(tag = 1)]
+    Refreshed(RegisterEvent),
+}
+
+#[derive(Debug, PartialEq, Eq, Reject, Serialize, SchemaType)]
+pub enum FactoryError {
+    /// Failed parsing the parameter.
+    #[from(ParseError)]
+    ParseParams,
+    /// The contract does not answer `view_owners` like a wallet.
+    NotAWallet,
+    /// The wallet is already registered.
+    WalletAlreadyRegistered,
+    /// The wallet is not registered.
+    WalletNotRegistered,
+    /// Querying the owners of the wallet failed.
+    InvokeContractError,
+    /// Failed logging: Log is full.
+    LogFull,
+    /// Failed logging: Log is malformed.
+    LogMalformed,
+}
+
+impl<T> From<CallContractError<T>> for FactoryError {
+    fn from(_cce: CallContractError<T>) -> Self {
+        Self::InvokeContractError
+    }
+}
+
+impl From<LogError> for FactoryError {
+    fn from(le: LogError) -> Self {
+        match le {
+            LogError::Full => Self::LogFull,
+            LogError::Malformed => Self::LogMalformed,
+        }
+    }
+}
+
+#[init(contract = "multisig_factory", event = "FactoryEvent")]
+pub fn factory_init<S: HasStateApi>(
+    _ctx: &impl HasInitContext,
+    state_builder: &mut StateBuilder<S>,
+) -> InitResult<FactoryState<S>> {
+    Ok(FactoryState {
+        wallets: state_builder.new_map(),
+        wallet_count: 0,
+        wallet_indices: state_builder.new_map(),
+        wallet_owners: state_builder.new_map(),
+        wallets_by_owner: state_builder.new_map(),
+    })
+}
+
+/// Register a wallet, indexed by its current owners. Can be called by anyone.
+#[receive(
+    contract = "multisig_factory",
+    name = "register_wallet",
+    parameter = "ContractAddress",
+    return_value = "WalletIndex",
+    mutable,
+    enable_logger,
+    error = "FactoryError"
+)]
+pub fn factory_register_wallet<S: HasStateApi>(
+    ctx: &impl HasReceiveContext,
+    host: &mut impl HasHost<FactoryState<S>, StateApiType = S>,
+    logger: &mut impl HasLogger,
+) -> Result<WalletIndex, FactoryError> {
+    let wallet: ContractAddress = ctx.parameter_cursor().get()?;
+
+    ensure!(
+        host.state().wallet_indices.get(&wallet).is_none(),
+        FactoryError::WalletAlreadyRegistered
+    );
+
+    let owners = query_owners(host, wallet).map_err(|_| FactoryError::NotAWallet)?;
+
+    let state = host.state_mut();
+    let index = state.wallet_count;
+    state.wallets.insert(index, wallet);
+    state.wallet_indices.insert(wallet, index);
+    state.wallet_count += 1;
+    index_owners(state, index, &owners);
+
+    logger.log(&FactoryEvent::Registered(RegisterEvent {
+        index,
+        wallet,
+        owners,
+    }))?;
+
+    Ok(index)
+}
+
+/// Index a registered wallet again under its current owners, e.g. after an
+/// owner rotated their key. Can be called by anyone.
+#[receive(
+    contract = "multisig_factory",
+    name = "refresh_wallet",
+    parameter = "ContractAddress",
+    mutable,
+    enable_logger,
+    error = "FactoryError"
+)]
+pub fn factory_refresh_wallet<S: HasStateApi>(
+    ctx: &impl HasReceiveContext,
+    host: &mut impl HasHost<FactoryState<S>, StateApiType = S>,
+    logger: &mut impl HasLogger,
+) -> Result<(), FactoryError> {
+    let wallet: ContractAddress = ctx.parameter_cursor().get()?;
+
+    let index = *host
+        .state()
+        .wallet_indices
+        .get(&wallet)
+        .ok_or(FactoryError::WalletNotRegistered)?;
+
+    let owners = query_owners(host, wallet)?;
+    index_owners(host.state_mut(), index, &owners);
+
+    logger.log(&FactoryEvent::Refreshed(RegisterEvent {
+        index,
+        wallet,
+        owners,
+    }))?;
+
+    Ok(())
+}
+
+/// Query the current owners of a wallet.
+fn query_owners<S: HasStateApi>(
+    host: &impl HasHost<FactoryState<S>, StateApiType = S>,
+    wallet: ContractAddress,
+) -> Result<BTreeSet<Address>, FactoryError> {
+    let owners = host
+        .invoke_contract_read_only(
+            &wallet,
+            &(),
+            EntrypointName::new_unchecked("view_owners"),
+            Amount::zero(),
+        )?
+        .ok_or(FactoryError::InvokeContractError)?
+        .get()?;
+
+    Ok(owners)
+}
+
+/// Replace the indexed owners of a wallet.
+fn index_owners<S: HasStateApi>(
+    state: &mut FactoryState<S>,
+    index: WalletIndex,
+    owners: &BTreeSet<Address>,
+) {
+    if let Some(old_owners) = state.wallet_owners.remove_and_get(&index) {
+        for owner in old_owners.iter() {
+            let mut wallets = match state.wallets_by_owner.get_mut(owner) {
+                Some(wallets) => wallets,
+                None => continue,
+            };
+            (*wallets).remove(&index);
+            let now_empty = wallets.is_empty();
+            drop(wallets);
+            if now_empty {
+                state.wallets_by_owner.remove(owner);
+            }
+        }
+    }
+
+    for owner in owners.iter() {
+        let mut wallets = state
+            .wallets_by_owner
+            .entry(*owner)
+            .or_insert(BTreeSet::new());
+        (*wallets).insert(index);
+    }
+    state.wallet_owners.insert(index, owners.clone());
+}
+
+/// View the registered wallets, oldest first.
+#[receive(
+    contract = "multisig_factory",
+    name = "view_wallets",
+    parameter = "ViewWalletsParams",
+    return_value = "ViewWalletsResponse",
+    error = "FactoryError"
+)]
+pub fn factory_view_wallets<S: HasStateApi>(
+    ctx: &impl HasReceiveContext,
+    host: &impl HasHost<FactoryState<S>, StateApiType = S>,
+) -> Result<ViewWalletsResponse, FactoryError> {
+    let params: ViewWalletsParams = ctx.parameter_cursor().get()?;
+    let state = host.state();
+
+    let limit = u64::from(params.limit.min(MAX_WALLET_PAGE_SIZE));
+    let from = params.skip.min(state.wallet_count);
+    let to = from.saturating_add(limit).min(state.wallet_count);
+
+    let wallets = (from..to)
+        .filter_map(|index| state.wallets.get(&index).map(|wallet| *wallet))
+        .collect();
+
+    Ok(ViewWalletsResponse {
+        total: state.wallet_count,
+        wallets,
+    })
+}
+
+/// View the wallets of an owner, oldest first.
+#[receive(
+    contract = "multisig_factory",
+    name = "view_wallets_of_owner",
+    parameter = "Address",
+    return_value = "Vec<ContractAddress>",
+    error = "FactoryError"
+)]
+pub fn factory_view_wallets_of_owner<S: HasStateApi>(
+    ctx: &impl HasReceiveContext,
+    host: &impl HasHost<FactoryState<S>, StateApiType = S>,
+) -> Result<Vec<ContractAddress>, FactoryError> {
+    let owner: Address = ctx.parameter_cursor().get()?;
+    let state = host.state();
+
+    let wallets = match state.wallets_by_owner.get(&owner) {
+        None => Vec::new(),
+        Some(indices) => indices
+            .iter()
+            .filter_map(|index| state.wallets.get(index).map(|wallet| *wallet))
+            .collect(),
+    };
+
+    Ok(wallets)
+}
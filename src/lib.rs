@@ -5,180 +5,2518 @@ use concordium_std::collections::*;
 use concordium_std::*;
 use core::fmt::Debug;
 
-/// How many of the owners need to agree before transfer
+pub mod factory;
+
+/// How many of the owners need to agree before transfer, unless a request
+/// asks for more
 pub const TRANSFER_AGREEMENT_THRESHOLD: usize = 3;
 
-// Types
-pub type TransferRequestId = u128;
+/// Maximum length in bytes of the memo attached to a request.
+pub const MAX_MEMO_LENGTH: usize = 256;
+
+/// Maximum number of entries returned by `view_history` at once.
+pub const MAX_HISTORY_PAGE_SIZE: u32 = 100;
+
+/// Length of the rolling window the daily limit applies to.
+pub const DAILY_LIMIT_WINDOW: Duration = Duration::from_millis(24 * 60 * 60 * 1000);
+
+// Types
+pub type TransferRequestId = u128;
+pub type GovernanceProposalId = u64;
+pub type RoleSet = BTreeSet<Role>;
+
+/// Permissions which can be granted to addresses that are not owners.
+#[derive(Serialize, SchemaType, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Role {
+    /// Can submit transfer requests.
+    Proposer,
+    /// Can support, withdraw support from and veto transfer requests.
+    Approver,
+    /// Can execute queued transfer requests.
+    Executor,
+}
+
+/// Where the CCD of a transfer goes.
+#[derive(Serialize, SchemaType, Clone, PartialEq, Eq, Debug)]
+pub enum Receiver {
+    Account(AccountAddress),
+    /// A contract entrypoint, invoked with the amount attached and an empty
+    /// parameter.
+    Contract(ContractAddress, OwnedEntrypointName),
+}
+
+impl Receiver {
+    pub fn address(&self) -> Address {
+        match self {
+            Receiver::Account(account) => Address::Account(*account),
+            Receiver::Contract(address, _) => Address::Contract(*address),
+        }
+    }
+}
+
+/// What a transfer request is for.
+#[derive(Serialize, SchemaType, Clone, PartialEq, Eq, Debug)]
+pub enum RequestKind {
+    /// A transfer proposed by an owner.
+    Standard,
+    /// A depositor reclaiming part of their deposits. Executing it reduces
+    /// the recorded deposits of the target account.
+    Refund,
+    /// A recurring payment. Executing it activates the schedule, after
+    /// which anyone can trigger the payments as they fall due.
+    Recurring(RecurringSchedule),
+}
+
+#[derive(Serialize, SchemaType, Clone, PartialEq, Eq, Debug)]
+pub struct RecurringSchedule {
+    /// Time between two payments.
+    pub interval: Duration,
+    /// How many payments to make in total.
+    pub occurrences: u32,
+}
+
+/// A calendar month in UTC.
+#[derive(Serialize, SchemaType, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub struct CalendarMonth {
+    pub year: u32,
+    /// From 1 (January) to 12 (December).
+    pub month: u8,
+}
+
+impl CalendarMonth {
+    /// The month containing the given time.
+    pub fn of(time: Timestamp) -> Self {
+        // Count days from 0000-03-01 so that leap days fall at the end of a
+        // year, then split them into 400-year eras of equal length.
+        let days = time.timestamp_millis() / (24 * 60 * 60 * 1000) + 719_468;
+        let era = days / 146_097;
+        let day_of_era = days % 146_097;
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        // Months counted from March.
+        let shifted_month = (5 * day_of_year + 2) / 153;
+        let month = if shifted_month < 10 {
+            shifted_month + 3
+        } else {
+            shifted_month - 9
+        };
+        let year = era * 400 + year_of_era + u64::from(month <= 2);
+
+        CalendarMonth {
+            year: year as u32,
+            month: month as u8,
+        }
+    }
+}
+
+/// An owner's approval power, temporarily given to another account.
+#[derive(Serialize, SchemaType, Clone)]
+pub struct Delegation {
+    pub delegate: AccountAddress,
+    /// The delegation has no effect from this time onwards.
+    pub expiry: Timestamp,
+}
+
+/// A transfer which has been made.
+#[derive(Serialize, SchemaType, Clone)]
+pub struct ExecutedTransfer {
+    /// Block time of the execution.
+    pub executed_at: Timestamp,
+    /// Who executed the request, or made the quick transfer.
+    pub executor: Address,
+    pub transfer_amount: Amount,
+    pub receiver: Receiver,
+    pub memo: Option<String>,
+}
+
+/// An approved recurring payment.
+#[derive(Serialize, SchemaType, Clone)]
+pub struct ScheduledPayment {
+    pub transfer_amount: Amount,
+    pub receiver: Receiver,
+    pub interval: Duration,
+    /// How many payments are left.
+    pub remaining_occurrences: u32,
+    /// When the next payment can be triggered.
+    pub next_due: Timestamp,
+    /// The memo of the request, recorded with each payment.
+    pub memo: Option<String>,
+}
+
+/// Where a request is in its lifecycle. Executed and cancelled requests are
+/// removed from the state, so those statuses are only reported in events.
+///
+/// Approved and expired are computed from the block time when a request is
+/// viewed, not when they start to hold, so no event reports them, except
+/// expired when an expired request is evicted. Entrypoints that find a request
+/// expired fail, which reverts their logs. Indexers should derive both from the
+/// `Queued` event and the `executable_at` and `expiry` in `view_transfer_request`.
+#[derive(Serialize, SchemaType, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RequestStatus {
+    /// Collecting support, or vetoed.
+    Pending,
+    /// Has enough support and the execution delay has passed, so it can be
+    /// executed. Never stored, see `TransferRequest::status_at`.
+    Approved,
+    /// Has enough support, waiting for the execution delay to pass.
+    Queued,
+    /// The request was executed and moved into the history.
+    Executed,
+    Cancelled,
+    /// The expiry has passed before execution. Never stored, see
+    /// `TransferRequest::status_at`.
+    Expired,
+}
+
+#[derive(Serialize, SchemaType, Clone)]
+pub struct TransferRequest {
+    pub status: RequestStatus,
+    pub kind: RequestKind,
+    pub transfer_amount: Amount,
+    pub receiver: Receiver,
+    pub supporters: BTreeSet<Address>,
+    /// Owners who vetoed this request. A vetoed request cannot be queued
+    /// or executed until every rejector withdraws their veto.
+    pub rejectors: BTreeSet<Address>,
+    /// When the request reached the threshold, it is queued and can be
+    /// executed from this time onwards. `None` while not queued.
+    pub executable_at: Option<Timestamp>,
+    /// Why the transfer is made, for the on-chain audit trail.
+    pub memo: Option<String>,
+    /// How many supporters the request needs. At least the threshold of the
+    /// wallet.
+    pub threshold: u32,
+    /// The request can no longer be supported or executed after this time.
+    pub expiry: Option<Timestamp>,
+}
+
+impl TransferRequest {
+    fn is_expired(&self, now: Timestamp) -> bool {
+        self.expiry.map_or(false, |expiry| now >= expiry)
+    }
+
+    /// The status at the given time. The stored status only changes when
+    /// the request is acted on, so this also accounts for the expiry and the
+    /// execution delay passing.
+    pub fn status_at(&self, now: Timestamp) -> RequestStatus {
+        match self.status {
+            _ if self.is_expired(now) => RequestStatus::Expired,
+            RequestStatus::Queued
+                if self
+                    .executable_at
+                    .map_or(false, |executable_at| now >= executable_at) =>
+            {
+                RequestStatus::Approved
+            }
+            status => status,
+        }
+    }
+
+    /// Queue the request if it has enough support and no vetoes, otherwise
+    /// take it out of the queue. An already queued request keeps its
+    /// original `executable_at`. Returns the new status if it changed.
+    fn update_queue(&mut self, now: Timestamp, execution_delay: Duration) -> Option<RequestStatus> {
+        let status =
+            if self.supporters.len() >= self.threshold as usize && self.rejectors.is_empty() {
+                if self.executable_at.is_none() {
+                    self.executable_at = now.checked_add(execution_delay);
+                }
+                RequestStatus::Queued
+            } else {
+                self.executable_at = None;
+                RequestStatus::Pending
+            };
+
+        if status == self.status {
+            return None;
+        }
+        self.status = status;
+        Some(status)
+    }
+}
+
+/// Changes to the wallet configuration which need the agreement of the owners.
+#[derive(Serialize, SchemaType, Clone, PartialEq, Eq, Debug)]
+pub enum GovernanceAction {
+    /// Set the amount a single owner can send within the daily window.
+    SetDailyLimit(Amount),
+    /// Lift a pause set by one of the owners.
+    Unpause,
+    /// Grant a role to an address.
+    GrantRole { address: Address, role: Role },
+    /// Revoke a role from an address.
+    RevokeRole { address: Address, role: Role },
+    /// Set how many supporters a request needs by default.
+    SetThreshold(u32),
+    /// Replace the limits on what requests can be submitted.
+    SetTransferPolicy(TransferPolicy),
+}
+
+/// Limits on the transfers owners can request, on top of the approvals.
+/// Refunds of deposits are not limited.
+#[derive(Serialize, SchemaType, Clone, Default, PartialEq, Eq, Debug)]
+pub struct TransferPolicy {
+    /// The largest amount a single transfer can send, if limited.
+    pub max_transfer_amount: Option<Amount>,
+    /// The only receivers transfers can go to, if limited.
+    pub receiver_whitelist: Option<BTreeSet<Address>>,
+}
+
+#[derive(Serialize, SchemaType, Clone)]
+pub struct GovernanceProposal {
+    pub action: GovernanceAction,
+    pub supporters: BTreeSet<Address>,
+}
+
+///smart contract state.
+#[derive(Serial, DeserialWithState)]
+#[concordium(state_parameter = "S")]
+pub struct State<S> {
+    /// Who is authorized to sig (must be non-empty)
+    pub owners: BTreeSet<Address>,
+
+    /// How many supporters a request needs by default.
+    pub threshold: u32,
+
+    ///The id assigned to last request
+    pub last_request_id: TransferRequestId,
+
+    /// Requests which have not been dropped due to timing out or due to being
+    /// agreed to yet The request ID, the associated amount, when it times
+    /// out, who is making the transfer and which account owners support
+    /// this transfer
+    pub requests: StateMap<TransferRequestId, TransferRequest, S>,
+
+    /// How long a request has to stay queued after reaching the threshold
+    /// before it can be executed. Gives owners time to veto.
+    pub execution_delay: Duration,
+
+    /// How much a single owner can send with `quick_transfer` within
+    /// `DAILY_LIMIT_WINDOW`.
+    pub daily_limit: Amount,
+    /// Amount sent with `quick_transfer` in the current window.
+    pub daily_spent: Amount,
+    /// When the current daily window started.
+    pub daily_window_start: Timestamp,
+
+    /// The id assigned to the last governance proposal.
+    pub last_proposal_id: GovernanceProposalId,
+    /// Governance proposals which have not been executed yet.
+    pub proposals: StateMap<GovernanceProposalId, GovernanceProposal, S>,
+
+    /// Public keys owners sign off-chain approvals with.
+    pub owner_keys: StateMap<Address, PublicKeyEd25519, S>,
+    /// Nonce the next batch of off-chain approvals must be signed with.
+    pub signature_nonce: u64,
+
+    /// Hash identifying the chain the wallet runs on, usually the genesis
+    /// block hash. Off-chain approvals are signed under this context.
+    pub chain_context: HashSha2256,
+    /// The nonce the next permit of each owner must be signed with.
+    pub nonces: StateMap<AccountAddress, u64, S>,
+
+    /// How much each account has deposited and not been refunded.
+    pub deposits: StateMap<AccountAddress, Amount, S>,
+    /// The amount of the pending refund of each account. An account has at
+    /// most one pending refund.
+    pub pending_refunds: StateMap<AccountAddress, Amount, S>,
+
+    /// Active recurring payments, by the id of the request which created them.
+    pub scheduled_payments: StateMap<TransferRequestId, ScheduledPayment, S>,
+
+    /// While paused, no transfers can be requested, supported or made.
+    pub paused: bool,
+
+    /// Executed requests and quick transfers which are still retained, in
+    /// order of execution, by position.
+    pub history: StateMap<u64, HistoryEntry, S>,
+    /// Position of the oldest retained entry in `history`.
+    pub history_start: u64,
+    /// Position the next transfer is recorded at.
+    pub history_end: u64,
+    /// How many transfers to retain. `None` retains all of them.
+    pub history_retention: Option<u64>,
+
+    /// Roles granted to addresses which are not necessarily owners.
+    pub roles: StateMap<Address, RoleSet, S>,
+
+    /// Hashes of the active requests, to detect duplicate submissions.
+    pub request_hashes: StateMap<HashSha2256, TransferRequestId, S>,
+
+    /// Delegations of approval power, by the owner who delegated.
+    pub delegations: StateMap<Address, Delegation, S>,
+
+    /// Total CCD sent to each receiver.
+    pub spent_per_receiver: StateMap<Address, Amount, S>,
+    /// Total CCD sent in each calendar month.
+    pub spent_per_month: StateMap<CalendarMonth, Amount, S>,
+
+    pub transfer_policy: TransferPolicy,
+
+    /// How many requests other than refunds can be pending at once.
+    pub max_pending_requests: u32,
+    /// Number of entries in `requests` which are not refunds.
+    pub request_count: u64,
+    /// Number of refunds in `requests`. They do not count towards
+    /// `max_pending_requests`, so depositors cannot crowd out the owners.
+    pub refund_count: u64,
+}
+
+#[derive(Serialize, SchemaType, Clone)]
+pub struct InitParams {
+    /// Who is authorized to sig (must be non-empty)
+    #[concordium(size_length = 1)]
+    pub owners: BTreeSet<Address>,
+    /// Delay between a request reaching the threshold and its execution.
+    pub execution_delay: Duration,
+    /// How much a single owner can send per day without approvals.
+    pub daily_limit: Amount,
+    /// Hash identifying the chain, usually the genesis block hash.
+    pub chain_context: HashSha2256,
+    /// How many transfers to retain in the history.
+    pub history_retention: Option<u64>,
+    /// How many requests other than refunds can be pending at once, at least
+    /// 1. Expired requests are evicted to make room for new ones.
+    pub max_pending_requests: u32,
+}
+
+#[derive(Serialize, SchemaType, Clone)]
+pub struct SubmitParams {
+    pub transfer_amount: Amount,
+    pub receiver: Receiver,
+    /// At most `MAX_MEMO_LENGTH` bytes.
+    pub memo: Option<String>,
+    /// Require more supporters than the threshold of the wallet. Clamped
+    /// between the threshold and the number of owners.
+    pub threshold: Option<u32>,
+    /// The request can no longer be supported or executed after this time.
+    pub expiry: Option<Timestamp>,
+}
+
+/// The message owners sign off-chain to approve a transfer request.
+#[derive(Serialize, SchemaType, Clone)]
+pub struct SignedApprovalMessage {
+    /// The chain the approval is meant for, see `InitParams::chain_context`.
+    pub chain_context: HashSha2256,
+    /// The wallet the approval is meant for.
+    pub contract_address: ContractAddress,
+    /// Must match the current `signature_nonce` of the wallet.
+    pub nonce: u64,
+    pub request: SubmitParams,
+}
+
+#[derive(Serialize, SchemaType, Clone)]
+pub struct OwnerSignature {
+    pub owner: Address,
+    pub signature: SignatureEd25519,
+}
+
+#[derive(Serialize, SchemaType, Clone)]
+pub struct SignedSubmitParams {
+    pub request: SubmitParams,
+    pub nonce: u64,
+    pub signatures: Vec<OwnerSignature>,
+}
+
+/// The message an owner signs for `permit`, as defined by CIS-3.
+#[derive(Serialize, SchemaType, Clone)]
+pub struct PermitMessage {
+    /// The wallet the permit is meant for.
+    pub contract_address: ContractAddress,
+    /// Must match the current nonce of the signer.
+    pub nonce: u64,
+    /// The permit cannot be used after this time.
+    pub timestamp: Timestamp,
+    /// The entrypoint to invoke on behalf of the signer.
+    pub entry_point: OwnedEntrypointName,
+    /// The serialized parameter of the entrypoint.
+    #[concordium(size_length = 2)]
+    pub payload: Vec<u8>,
+}
+
+#[derive(Serialize, SchemaType)]
+pub struct PermitParam {
+    pub signature: AccountSignatures,
+    pub signer: AccountAddress,
+    pub message: PermitMessage,
+}
+
+#[derive(Serialize, SchemaType)]
+pub struct SupportsPermitQueryParams {
+    #[concordium(size_length = 2)]
+    pub queries: Vec<OwnedEntrypointName>,
+}
+
+/// Identifier of a standard, as defined by CIS-0.
+#[derive(Serialize, SchemaType, Clone, PartialEq, Eq, Debug)]
+pub struct StandardIdentifierOwned {
+    #[concordium(size_length = 1)]
+    pub id: String,
+}
+
+#[derive(Serialize, SchemaType)]
+pub struct SupportsQueryParams {
+    #[concordium(size_length = 2)]
+    pub queries: Vec<StandardIdentifierOwned>,
+}
+
+#[derive(Serialize, SchemaType, PartialEq, Eq, Debug)]
+pub enum SupportResult {
+    NoSupport,
+    Support,
+    /// The standard is implemented by other contracts. Part of CIS-0, never
+    /// returned by this contract.
+    SupportBy {
+        #[concordium(size_length = 1)]
+        contracts: Vec<ContractAddress>,
+    },
+}
+
+#[derive(Serialize, SchemaType)]
+pub struct SupportsQueryResponse {
+    #[concordium(size_length = 2)]
+    pub results: Vec<SupportResult>,
+}
+
+#[derive(Serialize, SchemaType)]
+pub struct NonceOfQueryParams {
+    #[concordium(size_length = 2)]
+    pub queries: Vec<AccountAddress>,
+}
+
+#[derive(Serialize, SchemaType)]
+pub struct NonceOfQueryResponse {
+    #[concordium(size_length = 2)]
+    pub results: Vec<u64>,
+}
+
+#[derive(Serialize, SchemaType, Clone)]
+pub struct RecurringSubmitParams {
+    pub transfer_amount: Amount,
+    pub receiver: Receiver,
+    pub schedule: RecurringSchedule,
+    /// At most `MAX_MEMO_LENGTH` bytes.
+    pub memo: Option<String>,
+    /// The request can no longer be supported or executed after this time.
+    pub expiry: Option<Timestamp>,
+}
+
+#[derive(Serialize, SchemaType, Clone)]
+pub struct ViewHistoryParams {
+    /// How many of the oldest retained entries to skip.
+    pub skip: u64,
+    /// How many entries to return, at most `MAX_HISTORY_PAGE_SIZE`.
+    pub limit: u32,
+}
+
+#[derive(Serialize, SchemaType, Clone)]
+pub struct HistoryEntry {
+    /// `None` for quick transfers.
+    pub request_id: Option<TransferRequestId>,
+    pub transfer: ExecutedTransfer,
+}
+
+#[derive(Serialize, SchemaType)]
+pub struct ViewHistoryResponse {
+    /// Number of retained entries in total.
+    pub total: u64,
+    pub entries: Vec<HistoryEntry>,
+}
+
+#[derive(Serialize, SchemaType)]
+pub struct SpendingStatsParams {
+    pub receivers: Vec<Address>,
+    pub months: Vec<CalendarMonth>,
+}
+
+/// The totals sent to the queried receivers and in the queried months, in
+/// the order of the query.
+#[derive(Serialize, SchemaType)]
+pub struct SpendingStatsResponse {
+    pub per_receiver: Vec<(Address, Amount)>,
+    pub per_month: Vec<(CalendarMonth, Amount)>,
+}
+
+/// Sizes of the collections in the state.
+#[derive(Serialize, SchemaType)]
+pub struct StateStats {
+    /// Stored requests other than refunds. Executed and cancelled requests
+    /// are removed, so these are all pending.
+    pub pending_requests: u64,
+    pub max_pending_requests: u32,
+    pub pending_refunds: u64,
+    /// Retained entries in the history.
+    pub history: u64,
+    pub owners: u32,
+}
+
+#[derive(Serialize, SchemaType, Clone)]
+pub struct QuickTransferParams {
+    pub transfer_amount: Amount,
+    pub target_account: AccountAddress,
+}
+
+#[derive(Debug, Serialize, SchemaType)]
+pub struct NonceEvent {
+    pub account: AccountAddress,
+    pub nonce: u64,
+}
+
+#[derive(Debug, Serialize, SchemaType)]
+pub struct DepositEvent {
+    pub depositor: Address,
+    pub amount: Amount,
+}
+
+#[derive(Debug, Serialize, SchemaType)]
+pub struct SubmitEvent {
+    pub request_id: TransferRequestId,
+    pub status: RequestStatus,
+    pub kind: RequestKind,
+    pub transfer_amount: Amount,
+    pub receiver: Receiver,
+    pub memo: Option<String>,
+}
+
+#[derive(Debug, Serialize, SchemaType)]
+pub struct StatusEvent {
+    pub request_id: TransferRequestId,
+    pub status: RequestStatus,
+}
+
+#[derive(Debug, Serialize, SchemaType)]
+pub struct QuickTransferEvent {
+    pub sender: Address,
+    pub transfer_amount: Amount,
+    pub target_account: AccountAddress,
+}
+
+#[derive(Debug, Serialize, SchemaType)]
+#[concordium(repr(u8))]
+pub enum Event {
+    /// CCD was deposited into the wallet.
+    #[concordium(tag = 0)]
+    Deposit(DepositEvent),
+    /// A transfer request was submitted.
+    #[concordium(tag = 1)]
+    Submitted(SubmitEvent),
+    /// The stored status of a transfer request changed.
+    #[concordium(tag = 2)]
+    StatusChanged(StatusEvent),
+    /// CCD was sent with `quick_transfer`.
+    #[concordium(tag = 3)]
+    QuickTransfer(QuickTransferEvent),
+    /// A permit was executed. Uses the tag required by CIS-3.
+    #[concordium(tag = 250)]
+    Nonce(NonceEvent),
+}
+
+#[derive(Debug, PartialEq, Eq, Reject, Serialize, SchemaType)]
+pub enum Error {
+    /// Failed parsing the parameter.
+    #[from(ParseError)]
+    ParseParams,
+
+    /// Not enough account owners.
+    InsufficientOwners,
+    /// Only account owners, or addresses with the required role, can
+    /// interact with this contract.
+    NotOwner,
+    /// Sender cannot be a contract.
+    ContractSender,
+    /// Not enough available funds for the requested transfer.
+    InsufficientAvailableFunds,
+    /// Not such request funds.
+    RequestNotFound,
+    /// A request with this ID already exists.
+    RequestAlreadyExists,
+    /// Transfer amount or account is different from the request.
+    MismatchingRequestInformation,
+    /// You have already supported this transfer.
+    RequestAlreadySupported,
+    /// You have not already supported this transfer.
+    RequestAlreadyNotSupported,
+    /// All owners have not supported the request
+    RequestNotSupportedByAllOwners,
+    /// The request has not reached the threshold and is not queued.
+    RequestNotQueued,
+    /// The execution delay of the queued request has not passed yet.
+    TimelockNotExpired,
+    /// You have already vetoed this request.
+    RequestAlreadyVetoed,
+    /// The request has been vetoed by at least one owner.
+    RequestVetoed,
+    /// The transfer would exceed the daily limit.
+    DailyLimitExceeded,
+    /// No such governance proposal.
+    ProposalNotFound,
+    /// You have already supported this proposal.
+    ProposalAlreadySupported,
+    /// Not enough owners have supported the proposal.
+    ProposalNotSupportedByEnoughOwners,
+    /// No signatures were provided.
+    MissingSignatures,
+    /// The owner has not registered a public key.
+    OwnerKeyNotRegistered,
+    /// A signature could not be verified.
+    InvalidSignature,
+    /// The nonce does not match the expected nonce.
+    NonceMismatch,
+    /// The permit is meant for another contract.
+    WrongContract,
+    /// The permit has expired.
+    PermitExpired,
+    /// The entrypoint cannot be invoked with a permit.
+    UnsupportedPermitEntrypoint,
+    /// The refund exceeds the deposits of the account.
+    InsufficientDeposit,
+    /// The interval or number of occurrences of a schedule is zero.
+    InvalidSchedule,
+    /// No such scheduled payment.
+    ScheduledPaymentNotFound,
+    /// The next scheduled payment is not due yet.
+    PaymentNotDue,
+    /// The contract is paused.
+    ContractPaused,
+    /// The proposal is for a different action.
+    WrongProposalAction,
+    /// The memo is longer than `MAX_MEMO_LENGTH`.
+    MemoTooLong,
+    /// The threshold is zero or larger than the number of owners.
+    InvalidThreshold,
+    /// The request has expired.
+    RequestExpired,
+    /// The delegation has already expired, the delegate can already approve
+    /// on its own, or the delegate already acts for another owner.
+    InvalidDelegation,
+    /// You have not delegated your approval power.
+    DelegationNotFound,
+    /// The address is already an owner.
+    AlreadyOwner,
+    /// The maximum number of pending requests is reached, and none of them
+    /// has expired.
+    TooManyPendingRequests,
+    /// The amount is above the maximum of a single transfer.
+    TransferAmountTooHigh,
+    /// The receiver is not on the whitelist.
+    ReceiverNotWhitelisted,
+    /// The amount is zero.
+    InvalidAmount,
+    /// The account already has a pending refund.
+    RefundAlreadyPending,
+    /// The maximum number of pending requests is zero.
+    InvalidMaxPendingRequests,
+
+    /// Invalid receiver when invoking a transfer.
+    InvokeTransferMissingAccount,
+    /// Insufficient funds when invoking a transfer.
+    InvokeTransferInsufficientFunds,
+    /// Invoking a receiving contract failed.
+    InvokeContractError,
+
+    /// Failed logging: Log is full.
+    LogFull,
+    /// Failed logging: Log is malformed.
+    LogMalformed,
+}
+
+/// Mapping errors related to transfer invocations to CustomContractError.
+impl From<TransferError> for Error {
+    fn from(te: TransferError) -> Self {
+        match te {
+            TransferError::AmountTooLarge => Self::InvokeTransferInsufficientFunds,
+            TransferError::MissingAccount => Self::InvokeTransferMissingAccount,
+        }
+    }
+}
+
+/// Mapping errors related to contract invocations to Error.
+impl<T> From<CallContractError<T>> for Error {
+    fn from(_cce: CallContractError<T>) -> Self {
+        Self::InvokeContractError
+    }
+}
+
+/// Mapping the logging errors to Error.
+impl From<LogError> for Error {
+    fn from(le: LogError) -> Self {
+        match le {
+            LogError::Full => Self::LogFull,
+            LogError::Malformed => Self::LogMalformed,
+        }
+    }
+}
+
+/// The standards reported by `supports`.
+const SUPPORTED_STANDARDS: [&str; 2] = ["CIS-0", "CIS-3"];
+
+/// The entrypoints that can be invoked with `permit`.
+const SUPPORTS_PERMIT_ENTRYPOINTS: [EntrypointName; 2] = [
+    EntrypointName::new_unchecked("support_transfer_request"),
+    EntrypointName::new_unchecked("not_support_transfer_request"),
+];
+
+fn is_owner(address: Address, owners: &BTreeSet<Address>) -> bool {
+    owners.contains(&address)
+}
+
+/// Replace `old` by `new` in the set, if it contains `old`.
+fn replace_address(set: &mut BTreeSet<Address>, old: Address, new: Address) {
+    if set.remove(&old) {
+        set.insert(new);
+    }
+}
+
+/// Owners have every role, other addresses only the roles granted to them.
+fn has_role<S: HasStateApi>(state: &State<S>, address: Address, role: Role) -> bool {
+    is_owner(address, &state.owners)
+        || state
+            .roles
+            .get(&address)
+            .map_or(false, |roles| roles.contains(&role))
+}
+
+/// The address whose approval the sender gives: the sender itself if it can
+/// approve, otherwise the owner who delegated their approval power to it.
+fn approver_for<S: HasStateApi>(
+    state: &State<S>,
+    sender: Address,
+    now: Timestamp,
+) -> Option<Address> {
+    if has_role(state, sender, Role::Approver) {
+        return Some(sender);
+    }
+    match sender {
+        Address::Account(delegate) => active_delegator(state, delegate, now),
+        Address::Contract(_) => None,
+    }
+}
+
+/// The owner with an unexpired delegation to the given account, if any.
+fn active_delegator<S: HasStateApi>(
+    state: &State<S>,
+    delegate: AccountAddress,
+    now: Timestamp,
+) -> Option<Address> {
+    state.owners.iter().copied().find(|owner| {
+        state.delegations.get(owner).map_or(false, |delegation| {
+            delegation.delegate == delegate && now < delegation.expiry
+        })
+    })
+}
+
+/// The threshold of a new request, given the threshold asked for at
+/// submission.
+fn request_threshold<S: HasStateApi>(state: &State<S>, requested: Option<u32>) -> u32 {
+    let max = state.owners.len() as u32;
+    requested.map_or(state.threshold, |threshold| {
+        threshold.min(max).max(state.threshold)
+    })
+}
+
+/// A threshold must be reachable by the owners and at least one.
+fn is_valid_threshold(threshold: u32, owners: &BTreeSet<Address>) -> bool {
+    threshold >= 1 && threshold as usize <= owners.len()
+}
+
+fn is_valid_memo(memo: &Option<String>) -> bool {
+    memo.as_ref()
+        .map_or(true, |memo| memo.len() <= MAX_MEMO_LENGTH)
+}
+
+fn submit_event(request_id: TransferRequestId, request: &TransferRequest) -> Event {
+    Event::Submitted(SubmitEvent {
+        request_id,
+        status: request.status,
+        kind: request.kind.clone(),
+        transfer_amount: request.transfer_amount,
+        receiver: request.receiver.clone(),
+        memo: request.memo.clone(),
+    })
+}
+
+/// Log the new status of a request, if it changed.
+fn log_status_change(
+    logger: &mut impl HasLogger,
+    request_id: TransferRequestId,
+    status: Option<RequestStatus>,
+) -> Result<(), Error> {
+    if let Some(status) = status {
+        logger.log(&Event::StatusChanged(StatusEvent { request_id, status }))?;
+    }
+
+    Ok(())
+}
+
+/// Identifies requests which would make the same payment, computed over the
+/// kind, amount, receiver, memo and expiry of the request.
+fn request_hash(
+    request: &TransferRequest,
+    crypto_primitives: &impl HasCryptoPrimitives,
+) -> HashSha2256 {
+    let mut data = to_bytes(&request.kind);
+    data.extend_from_slice(&to_bytes(&request.transfer_amount));
+    data.extend_from_slice(&to_bytes(&request.receiver));
+    data.extend_from_slice(&to_bytes(&request.memo));
+    data.extend_from_slice(&to_bytes(&request.expiry));
+    crypto_primitives.hash_sha2_256(&data)
+}
+
+/// Store a new request, unless an active request would make the same payment.
+/// If the maximum of pending requests is reached, the oldest expired request
+/// is removed to make room.
+fn insert_request<S: HasStateApi>(
+    state: &mut State<S>,
+    logger: &mut impl HasLogger,
+    request_id: TransferRequestId,
+    request: TransferRequest,
+    now: Timestamp,
+    crypto_primitives: &impl HasCryptoPrimitives,
+) -> Result<(), Error> {
+    let is_refund = request.kind == RequestKind::Refund;
+    if !is_refund {
+        check_transfer_policy(
+            &state.transfer_policy,
+            request.receiver.address(),
+            request.transfer_amount,
+        )?;
+    }
+
+    let hash = request_hash(&request, crypto_primitives);
+    ensure!(
+        state.request_hashes.get(&hash).is_none(),
+        Error::RequestAlreadyExists
+    );
+
+    if !is_refund && state.request_count >= u64::from(state.max_pending_requests) {
+        evict_expired_request(state, logger, now, crypto_primitives)?;
+    }
+
+    state.request_hashes.insert(hash, request_id);
+    state.requests.insert(request_id, request);
+    if is_refund {
+        state.refund_count += 1;
+    } else {
+        state.request_count += 1;
+    }
+    state.last_request_id = request_id;
+
+    Ok(())
+}
+
+/// Remove a request from the state together with its entry in the hash index,
+/// once it is executed, cancelled or evicted.
+fn remove_request<S: HasStateApi>(
+    state: &mut State<S>,
+    request_id: TransferRequestId,
+    crypto_primitives: &impl HasCryptoPrimitives,
+) -> Option<TransferRequest> {
+    let request = state.requests.remove_and_get(&request_id)?;
+    state
+        .request_hashes
+        .remove(&request_hash(&request, crypto_primitives));
+
+    match (&request.kind, &request.receiver) {
+        (RequestKind::Refund, Receiver::Account(depositor)) => {
+            state.pending_refunds.remove(depositor);
+            state.refund_count -= 1;
+        }
+        _ => state.request_count -= 1,
+    }
+
+    Some(request)
+}
+
+/// Remove the oldest expired request from the state. Only called when the
+/// limit is reached, so this looks at `max_pending_requests` entries plus one
+/// pending refund per depositor at most. Refunds never expire.
+fn evict_expired_request<S: HasStateApi>(
+    state: &mut State<S>,
+    logger: &mut impl HasLogger,
+    now: Timestamp,
+    crypto_primitives: &impl HasCryptoPrimitives,
+) -> Result<(), Error> {
+    let request_id = state
+        .requests
+        .iter()
+        .filter(|(_, request)| request.is_expired(now))
+        .map(|(request_id, _)| *request_id)
+        .min()
+        .ok_or(Error::TooManyPendingRequests)?;
+
+    remove_request(state, request_id, crypto_primitives);
+
+    log_status_change(logger, request_id, Some(RequestStatus::Expired))
+}
+
+fn check_transfer_policy(
+    policy: &TransferPolicy,
+    receiver: Address,
+    amount: Amount,
+) -> Result<(), Error> {
+    if let Some(max_transfer_amount) = policy.max_transfer_amount {
+        ensure!(amount <= max_transfer_amount, Error::TransferAmountTooHigh);
+    }
+    if let Some(whitelist) = &policy.receiver_whitelist {
+        ensure!(whitelist.contains(&receiver), Error::ReceiverNotWhitelisted);
+    }
+
+    Ok(())
+}
+
+/// Record the support of an owner for a request and queue the request if it
+/// now has enough support.
+fn support_request<S: HasStateApi>(
+    state: &mut State<S>,
+    logger: &mut impl HasLogger,
+    supporter: Address,
+    request_id: TransferRequestId,
+    now: Timestamp,
+) -> Result<(), Error> {
+    ensure!(!state.paused, Error::ContractPaused);
+    let execution_delay = state.execution_delay;
+
+    let mut matching_request = state
+        .requests
+        .entry(request_id)
+        .occupied_or(Error::RequestNotFound)?;
+
+    ensure!(!matching_request.is_expired(now), Error::RequestExpired);
+    ensure!(
+        !matching_request.supporters.contains(&supporter),
+        Error::RequestAlreadySupported
+    );
+    matching_request.rejectors.remove(&supporter);
+    matching_request.supporters.insert(supporter);
+    let status = matching_request.update_queue(now, execution_delay);
+
+    log_status_change(logger, request_id, status)
+}
+
+/// Withdraw the support of an owner for a request, taking it out of the queue
+/// if it no longer has enough support.
+fn withdraw_support<S: HasStateApi>(
+    state: &mut State<S>,
+    logger: &mut impl HasLogger,
+    supporter: Address,
+    request_id: TransferRequestId,
+    now: Timestamp,
+) -> Result<(), Error> {
+    let execution_delay = state.execution_delay;
+
+    let mut matching_request = state
+        .requests
+        .entry(request_id)
+        .occupied_or(Error::RequestNotFound)?;
+
+    ensure!(!matching_request.is_expired(now), Error::RequestExpired);
+    ensure!(
+        matching_request.supporters.contains(&supporter),
+        Error::RequestAlreadyNotSupported
+    );
+    matching_request.supporters.remove(&supporter);
+    let status = matching_request.update_queue(now, execution_delay);
+
+    log_status_change(logger, request_id, status)
+}
+
+/// Move a queued request whose execution delay has passed into the history
+/// and make the transfer.
+fn execute_request<S: HasStateApi>(
+    host: &mut impl HasHost<State<S>, StateApiType = S>,
+    logger: &mut impl HasLogger,
+    request_id: TransferRequestId,
+    executor: Address,
+    now: Timestamp,
+    crypto_primitives: &impl HasCryptoPrimitives,
+) -> Result<(), Error> {
+    ensure!(!host.state().paused, Error::ContractPaused);
+
+    {
+        let matching_request = host
+            .state()
+            .requests
+            .get(&request_id)
+            .ok_or(Error::RequestNotFound)?;
+        ensure!(!matching_request.is_expired(now), Error::RequestExpired);
+        ensure!(matching_request.rejectors.is_empty(), Error::RequestVetoed);
+        let executable_at = matching_request
+            .executable_at
+            .ok_or(Error::RequestNotQueued)?;
+        ensure!(now >= executable_at, Error::TimelockNotExpired);
+    }
+
+    let matching_request = remove_request(host.state_mut(), request_id, crypto_primitives)
+        .ok_or(Error::RequestNotFound)?;
+    log_status_change(logger, request_id, Some(RequestStatus::Executed))?;
+
+    let TransferRequest {
+        kind,
+        receiver,
+        transfer_amount,
+        memo,
+        ..
+    } = matching_request;
+    // Activating a recurring payment moves no CCD, its payments are archived
+    // as they are triggered.
+    if !matches!(kind, RequestKind::Recurring(_)) {
+        archive_transfer(
+            host.state_mut(),
+            Some(request_id),
+            ExecutedTransfer {
+                executed_at: now,
+                executor,
+                transfer_amount,
+                receiver: receiver.clone(),
+                memo: memo.clone(),
+            },
+        );
+    }
+
+    match kind {
+        RequestKind::Standard => {
+            transfer_to(host, &receiver, transfer_amount, now)?;
+        }
+        RequestKind::Refund => {
+            let depositor = match receiver {
+                Receiver::Account(depositor) => depositor,
+                Receiver::Contract(..) => bail!(Error::InsufficientDeposit),
+            };
+            let mut deposit = host
+                .state_mut()
+                .deposits
+                .entry(depositor)
+                .occupied_or(Error::InsufficientDeposit)?;
+            ensure!(*deposit >= transfer_amount, Error::InsufficientDeposit);
+            *deposit = Amount::from_micro_ccd(deposit.micro_ccd - transfer_amount.micro_ccd);
+            drop(deposit);
+            record_spending(
+                host.state_mut(),
+                Address::Account(depositor),
+                transfer_amount,
+                now,
+            );
+            host.invoke_transfer(&depositor, transfer_amount)?;
+        }
+        RequestKind::Recurring(schedule) => {
+            host.state_mut().scheduled_payments.insert(
+                request_id,
+                ScheduledPayment {
+                    transfer_amount,
+                    receiver,
+                    interval: schedule.interval,
+                    remaining_occurrences: schedule.occurrences,
+                    next_due: now,
+                    memo,
+                },
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Send CCD to an account, or invoke a contract entrypoint with it.
+fn transfer_to<S: HasStateApi>(
+    host: &mut impl HasHost<State<S>, StateApiType = S>,
+    receiver: &Receiver,
+    amount: Amount,
+    now: Timestamp,
+) -> Result<(), Error> {
+    record_spending(host.state_mut(), receiver.address(), amount, now);
+
+    match receiver {
+        Receiver::Account(account) => host.invoke_transfer(account, amount)?,
+        Receiver::Contract(address, entrypoint) => {
+            host.invoke_contract(address, &(), entrypoint.as_entrypoint_name(), amount)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Add a transfer to the spending statistics.
+fn record_spending<S: HasStateApi>(
+    state: &mut State<S>,
+    receiver: Address,
+    amount: Amount,
+    now: Timestamp,
+) {
+    let mut receiver_total = state
+        .spent_per_receiver
+        .entry(receiver)
+        .or_insert(Amount::zero());
+    *receiver_total =
+        Amount::from_micro_ccd(receiver_total.micro_ccd.saturating_add(amount.micro_ccd));
+    drop(receiver_total);
+
+    let mut month_total = state
+        .spent_per_month
+        .entry(CalendarMonth::of(now))
+        .or_insert(Amount::zero());
+    *month_total = Amount::from_micro_ccd(month_total.micro_ccd.saturating_add(amount.micro_ccd));
+}
+
+/// Record a transfer in the history, dropping the oldest entries beyond the
+/// retention cap.
+fn archive_transfer<S: HasStateApi>(
+    state: &mut State<S>,
+    request_id: Option<TransferRequestId>,
+    transfer: ExecutedTransfer,
+) {
+    state.history.insert(
+        state.history_end,
+        HistoryEntry {
+            request_id,
+            transfer,
+        },
+    );
+    state.history_end += 1;
+
+    if let Some(retention) = state.history_retention {
+        while state.history_end - state.history_start > retention {
+            state.history.remove(&state.history_start);
+            state.history_start += 1;
+        }
+    }
+}
+
+/// Create a governance proposal supported by the proposer.
+fn submit_proposal<S: HasStateApi>(
+    state: &mut State<S>,
+    proposer: Address,
+    action: GovernanceAction,
+) -> GovernanceProposalId {
+    let proposal_id = state.last_proposal_id + 1;
+
+    let mut supporters = BTreeSet::new();
+    supporters.insert(proposer);
+
+    state
+        .proposals
+        .insert(proposal_id, GovernanceProposal { action, supporters });
+    state.last_proposal_id = proposal_id;
+
+    proposal_id
+}
+
+/// How many owners must support a proposal for the action. Changing the
+/// threshold needs every owner, so that a lowered threshold cannot be used to
+/// lower it further. Everything else, including unpausing, needs the
+/// threshold, so that a single owner who lost their key or pauses on purpose
+/// cannot lock the wallet.
+fn required_support<S: HasStateApi>(state: &State<S>, action: &GovernanceAction) -> usize {
+    match action {
+        GovernanceAction::SetThreshold(_) => state.owners.len(),
+        _ => state.threshold as usize,
+    }
+}
+
+/// Remove a governance proposal which is supported by enough owners and return
+/// its action.
+fn take_approved_proposal<S: HasStateApi>(
+    state: &mut State<S>,
+    proposal_id: GovernanceProposalId,
+) -> Result<GovernanceAction, Error> {
+    let proposal = state
+        .proposals
+        .remove_and_get(&proposal_id)
+        .ok_or(Error::ProposalNotFound)?;
+    ensure!(
+        proposal.supporters.len() >= required_support(state, &proposal.action),
+        Error::ProposalNotSupportedByEnoughOwners
+    );
+
+    Ok(proposal.action)
+}
+
+fn apply_governance_action<S: HasStateApi>(
+    state: &mut State<S>,
+    action: GovernanceAction,
+) -> Result<(), Error> {
+    match action {
+        GovernanceAction::SetDailyLimit(daily_limit) => state.daily_limit = daily_limit,
+        GovernanceAction::Unpause => state.paused = false,
+        GovernanceAction::GrantRole { address, role } => {
+            let mut roles = state.roles.entry(address).or_insert(BTreeSet::new());
+            (*roles).insert(role);
+        }
+        GovernanceAction::RevokeRole { address, role } => {
+            if let Some(mut roles) = state.roles.get_mut(&address) {
+                (*roles).remove(&role);
+            }
+        }
+        GovernanceAction::SetThreshold(threshold) => {
+            ensure!(
+                is_valid_threshold(threshold, &state.owners),
+                Error::InvalidThreshold
+            );
+            state.threshold = threshold;
+        }
+        GovernanceAction::SetTransferPolicy(policy) => state.transfer_policy = policy,
+    }
+
+    Ok(())
+}
+
+// Contract implementation
+//--------------- contract functions ----------
+#[init(
+    contract = "multisig_wallet",
+    parameter = "InitParams",
+    event = "Event",
+    payable
+)]
+#[inline(always)]
+pub fn contract_init<S: HasStateApi>(
+    ctx: &impl HasInitContext,
+    state_builder: &mut StateBuilder<S>,
+    _amount: Amount,
+) -> Result<State<S>, Error> {
+    let init_params: InitParams = ctx.parameter_cursor().get()?;
+    let owners = init_params.owners;
+    ensure!(
+        owners.len() == TRANSFER_AGREEMENT_THRESHOLD,
+        Error::InsufficientOwners
+    );
+    ensure!(
+        init_params.max_pending_requests > 0,
+        Error::InvalidMaxPendingRequests
+    );
+
+    let state = State {
+        owners,
+        threshold: TRANSFER_AGREEMENT_THRESHOLD as u32,
+        last_request_id: 0,
+        requests: state_builder.new_map(),
+        execution_delay: init_params.execution_delay,
+        daily_limit: init_params.daily_limit,
+        daily_spent: Amount::zero(),
+        daily_window_start: ctx.metadata().slot_time(),
+        last_proposal_id: 0,
+        proposals: state_builder.new_map(),
+        owner_keys: state_builder.new_map(),
+        signature_nonce: 0,
+        chain_context: init_params.chain_context,
+        nonces: state_builder.new_map(),
+        deposits: state_builder.new_map(),
+        pending_refunds: state_builder.new_map(),
+        scheduled_payments: state_builder.new_map(),
+        paused: false,
+        history: state_builder.new_map(),
+        history_start: 0,
+        history_end: 0,
+        history_retention: init_params.history_retention,
+        roles: state_builder.new_map(),
+        request_hashes: state_builder.new_map(),
+        delegations: state_builder.new_map(),
+        spent_per_receiver: state_builder.new_map(),
+        spent_per_month: state_builder.new_map(),
+        transfer_policy: TransferPolicy::default(),
+        max_pending_requests: init_params.max_pending_requests,
+        request_count: 0,
+        refund_count: 0,
+    };
+
+    Ok(state)
+}
+
+#[receive(
+    contract = "multisig_wallet",
+    name = "deposit",
+    payable,
+    mutable,
+    enable_logger,
+    error = "Error"
+)]
+fn contract_receive_deposit<S: HasStateApi>(
+    ctx: &impl HasReceiveContext,
+    host: &mut impl HasHost<State<S>, StateApiType = S>,
+    amount: Amount,
+    logger: &mut impl HasLogger,
+) -> Result<(), Error> {
+    let sender = ctx.sender();
+
+    // Only deposits from accounts can be refunded.
+    if let Address::Account(depositor) = sender {
+        let mut deposit = host
+            .state_mut()
+            .deposits
+            .entry(depositor)
+            .or_insert(Amount::zero());
+        *deposit = Amount::from_micro_ccd(deposit.micro_ccd + amount.micro_ccd);
+    }
+
+    logger.log(&Event::Deposit(DepositEvent {
+        depositor: sender,
+        amount,
+    }))?;
+
+    Ok(())
+}
+
+/// Request a refund of previous deposits. The refund is paid out like any
+/// other transfer request, once enough owners support it. An account can have
+/// one pending refund at a time, which cannot exceed its deposits.
+#[receive(
+    contract = "multisig_wallet",
+    name = "request_refund",
+    parameter = "Amount",
+    mutable,
+    enable_logger,
+    crypto_primitives,
+    error = "Error"
+)]
+pub fn contract_receive_request_refund<S: HasStateApi>(
+    ctx: &impl HasReceiveContext,
+    host: &mut impl HasHost<State<S>, StateApiType = S>,
+    logger: &mut impl HasLogger,
+    crypto_primitives: &impl HasCryptoPrimitives,
+) -> Result<TransferRequestId, Error> {
+    ensure!(!host.state().paused, Error::ContractPaused);
+
+    let depositor = match ctx.sender() {
+        Address::Contract(_) => bail!(Error::ContractSender),
+        Address::Account(account_address) => account_address,
+    };
+
+    let transfer_amount: Amount = ctx.parameter_cursor().get()?;
+    ensure!(transfer_amount > Amount::zero(), Error::InvalidAmount);
+
+    let deposit = host
+        .state()
+        .deposits
+        .get(&depositor)
+        .map_or(Amount::zero(), |deposit| *deposit);
+    ensure!(transfer_amount <= deposit, Error::InsufficientDeposit);
+    ensure!(
+        host.state().pending_refunds.get(&depositor).is_none(),
+        Error::RefundAlreadyPending
+    );
+
+    let req_id = host.state().last_request_id + 1;
+
+    let new_request = TransferRequest {
+        status: RequestStatus::Pending,
+        kind: RequestKind::Refund,
+        transfer_amount,
+        receiver: Receiver::Account(depositor),
+        supporters: BTreeSet::new(),
+        rejectors: BTreeSet::new(),
+        executable_at: None,
+        memo: None,
+        threshold: host.state().threshold,
+        expiry: None,
+    };
+
+    logger.log(&submit_event(req_id, &new_request))?;
+
+    insert_request(
+        host.state_mut(),
+        logger,
+        req_id,
+        new_request,
+        ctx.metadata().slot_time(),
+        crypto_primitives,
+    )?;
+    host.state_mut()
+        .pending_refunds
+        .insert(depositor, transfer_amount);
+
+    Ok(req_id)
+}
+
+#[receive(
+    contract = "multisig_wallet",
+    name = "submit_transfer_request",
+    parameter = "SubmitParams",
+    mutable,
+    enable_logger,
+    crypto_primitives,
+    error = "Error"
+)]
+pub fn contract_receive_submit_transfer_request<S: HasStateApi>(
+    ctx: &impl HasReceiveContext,
+    host: &mut impl HasHost<State<S>, StateApiType = S>,
+    logger: &mut impl HasLogger,
+    crypto_primitives: &impl HasCryptoPrimitives,
+) -> Result<TransferRequestId, Error> {
+    ensure!(!host.state().paused, Error::ContractPaused);
+
+    let sender = ctx.sender();
+
+    ensure!(
+        has_role(host.state(), sender, Role::Proposer),
+        Error::NotOwner
+    );
+
+    let submit_params: SubmitParams = ctx.parameter_cursor().get()?;
+    ensure!(is_valid_memo(&submit_params.memo), Error::MemoTooLong);
+
+    let req_id = host.state().last_request_id + 1;
+    let transfer_amount = submit_params.transfer_amount;
+    let receiver = submit_params.receiver;
+
+    // A proposer who cannot approve does not count as a supporter.
+    let mut supporters = BTreeSet::new();
+    if has_role(host.state(), sender, Role::Approver) {
+        supporters.insert(sender);
+    }
+
+    let mut new_request = TransferRequest {
+        status: RequestStatus::Pending,
+        kind: RequestKind::Standard,
+        transfer_amount,
+        receiver,
+        supporters,
+        rejectors: BTreeSet::new(),
+        executable_at: None,
+        memo: submit_params.memo,
+        threshold: request_threshold(host.state(), submit_params.threshold),
+        expiry: submit_params.expiry,
+    };
+    new_request.update_queue(ctx.metadata().slot_time(), host.state().execution_delay);
+
+    logger.log(&submit_event(req_id, &new_request))?;
+
+    insert_request(
+        host.state_mut(),
+        logger,
+        req_id,
+        new_request,
+        ctx.metadata().slot_time(),
+        crypto_primitives,
+    )?;
+
+    Ok(req_id)
+}
+
+/// Submit a request for a recurring payment. Once executed, the payments can
+/// be triggered with `trigger_scheduled_payment` without further approvals.
+#[receive(
+    contract = "multisig_wallet",
+    name = "submit_recurring_transfer_request",
+    parameter = "RecurringSubmitParams",
+    mutable,
+    enable_logger,
+    crypto_primitives,
+    error = "Error"
+)]
+pub fn contract_receive_submit_recurring_transfer_request<S: HasStateApi>(
+    ctx: &impl HasReceiveContext,
+    host: &mut impl HasHost<State<S>, StateApiType = S>,
+    logger: &mut impl HasLogger,
+    crypto_primitives: &impl HasCryptoPrimitives,
+) -> Result<TransferRequestId, Error> {
+    ensure!(!host.state().paused, Error::ContractPaused);
+
+    let sender = ctx.sender();
+
+    ensure!(
+        has_role(host.state(), sender, Role::Proposer),
+        Error::NotOwner
+    );
+
+    let submit_params: RecurringSubmitParams = ctx.parameter_cursor().get()?;
+    ensure!(is_valid_memo(&submit_params.memo), Error::MemoTooLong);
+    let schedule = submit_params.schedule;
+    ensure!(
+        schedule.interval.millis() > 0 && schedule.occurrences > 0,
+        Error::InvalidSchedule
+    );
+
+    let req_id = host.state().last_request_id + 1;
+
+    // A proposer who cannot approve does not count as a supporter.
+    let mut supporters = BTreeSet::new();
+    if has_role(host.state(), sender, Role::Approver) {
+        supporters.insert(sender);
+    }
+
+    let mut new_request = TransferRequest {
+        status: RequestStatus::Pending,
+        kind: RequestKind::Recurring(schedule),
+        transfer_amount: submit_params.transfer_amount,
+        receiver: submit_params.receiver,
+        supporters,
+        rejectors: BTreeSet::new(),
+        executable_at: None,
+        memo: submit_params.memo,
+        threshold: host.state().threshold,
+        expiry: submit_params.expiry,
+    };
+    new_request.update_queue(ctx.metadata().slot_time(), host.state().execution_delay);
+
+    logger.log(&submit_event(req_id, &new_request))?;
+
+    insert_request(
+        host.state_mut(),
+        logger,
+        req_id,
+        new_request,
+        ctx.metadata().slot_time(),
+        crypto_primitives,
+    )?;
+
+    Ok(req_id)
+}
+
+/// Make the next payment of an active recurring payment, if it is due, and
+/// record it in the history. Can be called by anyone.
+#[receive(
+    contract = "multisig_wallet",
+    name = "trigger_scheduled_payment",
+    parameter = "TransferRequestId",
+    mutable,
+    error = "Error"
+)]
+pub fn contract_receive_trigger_scheduled_payment<S: HasStateApi>(
+    ctx: &impl HasReceiveContext,
+    host: &mut impl HasHost<State<S>, StateApiType = S>,
+) -> Result<(), Error> {
+    ensure!(!host.state().paused, Error::ContractPaused);
+
+    let schedule_id: TransferRequestId = ctx.parameter_cursor().get()?;
+    let now = ctx.metadata().slot_time();
+
+    let mut payment = host
+        .state()
+        .scheduled_payments
+        .get(&schedule_id)
+        .map(|payment| payment.clone())
+        .ok_or(Error::ScheduledPaymentNotFound)?;
+    ensure!(now >= payment.next_due, Error::PaymentNotDue);
+
+    payment.remaining_occurrences -= 1;
+    if payment.remaining_occurrences == 0 {
+        host.state_mut().scheduled_payments.remove(&schedule_id);
+    } else {
+        // Overflowing the timestamp means the schedule can never fall due again.
+        payment.next_due = payment
+            .next_due
+            .checked_add(payment.interval)
+            .unwrap_or(Timestamp::from_timestamp_millis(u64::MAX));
+        host.state_mut()
+            .scheduled_payments
+            .insert(schedule_id, payment.clone());
+    }
+
+    archive_transfer(
+        host.state_mut(),
+        Some(schedule_id),
+        ExecutedTransfer {
+            executed_at: now,
+            executor: ctx.sender(),
+            transfer_amount: payment.transfer_amount,
+            receiver: payment.receiver.clone(),
+            memo: payment.memo,
+        },
+    );
+    transfer_to(host, &payment.receiver, payment.transfer_amount, now)?;
+
+    Ok(())
+}
+
+/// Stop an active recurring payment before its remaining payments are made.
+/// Like cancelling a request, a single owner can do this.
+#[receive(
+    contract = "multisig_wallet",
+    name = "cancel_scheduled_payment",
+    parameter = "TransferRequestId",
+    mutable,
+    enable_logger,
+    error = "Error"
+)]
+pub fn contract_receive_cancel_scheduled_payment<S: HasStateApi>(
+    ctx: &impl HasReceiveContext,
+    host: &mut impl HasHost<State<S>, StateApiType = S>,
+    logger: &mut impl HasLogger,
+) -> Result<(), Error> {
+    let sender = ctx.sender();
+    let owners = &host.state().owners;
+
+    ensure!(is_owner(sender, owners), Error::NotOwner);
+
+    let schedule_id: TransferRequestId = ctx.parameter_cursor().get()?;
+
+    host.state_mut()
+        .scheduled_payments
+        .remove_and_get(&schedule_id)
+        .ok_or(Error::ScheduledPaymentNotFound)?;
+
+    log_status_change(logger, schedule_id, Some(RequestStatus::Cancelled))
+}
+
+#[receive(
+    contract = "multisig_wallet",
+    name = "support_transfer_request",
+    parameter = "TransferRequestId",
+    mutable,
+    enable_logger,
+    error = "Error"
+)]
+pub fn contract_receive_support_transfer_request<S: HasStateApi>(
+    ctx: &impl HasReceiveContext,
+    host: &mut impl HasHost<State<S>, StateApiType = S>,
+    logger: &mut impl HasLogger,
+) -> Result<(), Error> {
+    let sender = ctx.sender();
+    let now = ctx.metadata().slot_time();
+
+    let approver = approver_for(host.state(), sender, now).ok_or(Error::NotOwner)?;
+
+    let request_id: TransferRequestId = ctx.parameter_cursor().get()?;
+
+    support_request(host.state_mut(), logger, approver, request_id, now)
+}
+
+#[receive(
+    contract = "multisig_wallet",
+    name = "not_support_transfer_request",
+    parameter = "TransferRequestId",
+    mutable,
+    enable_logger,
+    error = "Error"
+)]
+pub fn contract_receive_not_support_transfer_request<S: HasStateApi>(
+    ctx: &impl HasReceiveContext,
+    host: &mut impl HasHost<State<S>, StateApiType = S>,
+    logger: &mut impl HasLogger,
+) -> Result<(), Error> {
+    let sender = ctx.sender();
+    let now = ctx.metadata().slot_time();
+
+    let approver = approver_for(host.state(), sender, now).ok_or(Error::NotOwner)?;
+
+    let request_id: TransferRequestId = ctx.parameter_cursor().get()?;
+
+    withdraw_support(host.state_mut(), logger, approver, request_id, now)
+}
+
+/// Let another account support and withdraw support from requests on behalf
+/// of the sender until the given expiry. Replaces an earlier delegation. The
+/// delegate cannot be an owner or an approver, as it would only ever approve
+/// for itself.
+#[receive(
+    contract = "multisig_wallet",
+    name = "delegate_to",
+    parameter = "Delegation",
+    mutable,
+    error = "Error"
+)]
+pub fn contract_receive_delegate_to<S: HasStateApi>(
+    ctx: &impl HasReceiveContext,
+    host: &mut impl HasHost<State<S>, StateApiType = S>,
+) -> Result<(), Error> {
+    let sender = ctx.sender();
+    let owners = &host.state().owners;
+
+    ensure!(is_owner(sender, owners), Error::NotOwner);
+
+    let delegation: Delegation = ctx.parameter_cursor().get()?;
+    let now = ctx.metadata().slot_time();
+
+    ensure!(now < delegation.expiry, Error::InvalidDelegation);
+    ensure!(
+        !has_role(
+            host.state(),
+            Address::Account(delegation.delegate),
+            Role::Approver
+        ),
+        Error::InvalidDelegation
+    );
+    ensure!(
+        active_delegator(host.state(), delegation.delegate, now)
+            .map_or(true, |owner| owner == sender),
+        Error::InvalidDelegation
+    );
+
+    host.state_mut().delegations.insert(sender, delegation);
+
+    Ok(())
+}
+
+/// Revoke the delegation of the sender's approval power. Support given by the
+/// delegate so far is kept.
+#[receive(
+    contract = "multisig_wallet",
+    name = "revoke_delegation",
+    mutable,
+    error = "Error"
+)]
+pub fn contract_receive_revoke_delegation<S: HasStateApi>(
+    ctx: &impl HasReceiveContext,
+    host: &mut impl HasHost<State<S>, StateApiType = S>,
+) -> Result<(), Error> {
+    let sender = ctx.sender();
+    let owners = &host.state().owners;
+
+    ensure!(is_owner(sender, owners), Error::NotOwner);
+
+    host.state_mut()
+        .delegations
+        .remove_and_get(&sender)
+        .ok_or(Error::DelegationNotFound)?;
+
+    Ok(())
+}
+
+/// Veto a request. This takes the request out of the execution queue and
+/// blocks it until the veto is withdrawn by supporting the request again.
+#[receive(
+    contract = "multisig_wallet",
+    name = "veto_transfer_request",
+    parameter = "TransferRequestId",
+    mutable,
+    enable_logger,
+    error = "Error"
+)]
+pub fn contract_receive_veto_transfer_request<S: HasStateApi>(
+    ctx: &impl HasReceiveContext,
+    host: &mut impl HasHost<State<S>, StateApiType = S>,
+    logger: &mut impl HasLogger,
+) -> Result<(), Error> {
+    let sender = ctx.sender();
+
+    ensure!(
+        has_role(host.state(), sender, Role::Approver),
+        Error::NotOwner
+    );
+
+    let request_id: TransferRequestId = ctx.parameter_cursor().get()?;
+    let execution_delay = host.state().execution_delay;
+    let now = ctx.metadata().slot_time();
+
+    let mut matching_request = host
+        .state_mut()
+        .requests
+        .entry(request_id)
+        .occupied_or(Error::RequestNotFound)?;
+
+    ensure!(!matching_request.is_expired(now), Error::RequestExpired);
+    ensure!(
+        !matching_request.rejectors.contains(&sender),
+        Error::RequestAlreadyVetoed
+    );
+    matching_request.supporters.remove(&sender);
+    matching_request.rejectors.insert(sender);
+    let status = matching_request.update_queue(now, execution_delay);
+
+    log_status_change(logger, request_id, status)
+}
+
+#[receive(
+    contract = "multisig_wallet",
+    name = "execute_transfer_request",
+    parameter = "TransferRequestId",
+    mutable,
+    enable_logger,
+    crypto_primitives,
+    error = "Error"
+)]
+pub fn contract_receive_execute_transfer_request<S: HasStateApi>(
+    ctx: &impl HasReceiveContext,
+    host: &mut impl HasHost<State<S>, StateApiType = S>,
+    logger: &mut impl HasLogger,
+    crypto_primitives: &impl HasCryptoPrimitives,
+) -> Result<(), Error> {
+    let sender = ctx.sender();
+
+    ensure!(
+        has_role(host.state(), sender, Role::Executor),
+        Error::NotOwner
+    );
+
+    let request_id: TransferRequestId = ctx.parameter_cursor().get()?;
+
+    match host.state().requests.get(&request_id) {
+        None => Err(Error::RequestNotFound),
+        Some(matching_request) => {
+            ensure!(
+                matching_request.supporters.len() >= matching_request.threshold as usize,
+                Error::RequestNotSupportedByAllOwners
+            );
+            execute_request(
+                host,
+                logger,
+                request_id,
+                sender,
+                ctx.metadata().slot_time(),
+                crypto_primitives,
+            )
+        }
+    }
+}
+
+#[receive(
+    contract = "multisig_wallet",
+    name = "view_transfer_request",
+    parameter = "TransferRequestId",
+    mutable,
+    return_value = "TransferRequest",
+    error = "Error"
+)]
+pub fn contract_receive_view_transfer_request<S: HasStateApi>(
+    ctx: &impl HasReceiveContext,
+    host: &mut impl HasHost<State<S>, StateApiType = S>,
+) -> Result<TransferRequest, Error> {
+    let sender = ctx.sender();
+    let owners = &host.state().owners;
+
+    ensure!(is_owner(sender, owners), Error::NotOwner);
+
+    let request_id: TransferRequestId = ctx.parameter_cursor().get()?;
+
+    let mut matching_request = host
+        .state()
+        .requests
+        .get(&request_id)
+        .ok_or(Error::RequestNotFound)?
+        .clone();
+    matching_request.status = matching_request.status_at(ctx.metadata().slot_time());
+
+    Ok(matching_request)
+}
+
+/// Cancel a request for good. Unlike a veto, this cannot be withdrawn. The
+/// request is removed, so a request for the same payment can be submitted
+/// again.
+#[receive(
+    contract = "multisig_wallet",
+    name = "cancel_transfer_request",
+    parameter = "TransferRequestId",
+    mutable,
+    enable_logger,
+    crypto_primitives,
+    error = "Error"
+)]
+pub fn contract_receive_cancel_transfer_request<S: HasStateApi>(
+    ctx: &impl HasReceiveContext,
+    host: &mut impl HasHost<State<S>, StateApiType = S>,
+    logger: &mut impl HasLogger,
+    crypto_primitives: &impl HasCryptoPrimitives,
+) -> Result<(), Error> {
+    let sender = ctx.sender();
+    let owners = &host.state().owners;
+
+    ensure!(is_owner(sender, owners), Error::NotOwner);
+
+    let request_id: TransferRequestId = ctx.parameter_cursor().get()?;
+
+    remove_request(host.state_mut(), request_id, crypto_primitives)
+        .ok_or(Error::RequestNotFound)?;
+
+    log_status_change(logger, request_id, Some(RequestStatus::Cancelled))
+}
+
+/// View the owners of the wallet.
+#[receive(
+    contract = "multisig_wallet",
+    name = "view_owners",
+    return_value = "BTreeSet<Address>",
+    error = "Error"
+)]
+pub fn contract_receive_view_owners<S: HasStateApi>(
+    _ctx: &impl HasReceiveContext,
+    host: &impl HasHost<State<S>, StateApiType = S>,
+) -> Result<BTreeSet<Address>, Error> {
+    Ok(host.state().owners.clone())
+}
+
+/// View the sizes of the collections in the state.
+#[receive(
+    contract = "multisig_wallet",
+    name = "state_stats",
+    return_value = "StateStats",
+    error = "Error"
+)]
+pub fn contract_receive_state_stats<S: HasStateApi>(
+    _ctx: &impl HasReceiveContext,
+    host: &impl HasHost<State<S>, StateApiType = S>,
+) -> Result<StateStats, Error> {
+    let state = host.state();
+
+    Ok(StateStats {
+        pending_requests: state.request_count,
+        pending_refunds: state.refund_count,
+        max_pending_requests: state.max_pending_requests,
+        history: state.history_end - state.history_start,
+        owners: state.owners.len() as u32,
+    })
+}
+
+/// View the executed requests and quick transfers, oldest first.
+#[receive(
+    contract = "multisig_wallet",
+    name = "view_history",
+    parameter = "ViewHistoryParams",
+    return_value = "ViewHistoryResponse",
+    error = "Error"
+)]
+pub fn contract_receive_view_history<S: HasStateApi>(
+    ctx: &impl HasReceiveContext,
+    host: &impl HasHost<State<S>, StateApiType = S>,
+) -> Result<ViewHistoryResponse, Error> {
+    let params: ViewHistoryParams = ctx.parameter_cursor().get()?;
+    let state = host.state();
+
+    let limit = u64::from(params.limit.min(MAX_HISTORY_PAGE_SIZE));
+    let from = state.history_start.saturating_add(params.skip);
+    let to = from.saturating_add(limit).min(state.history_end);
+
+    let entries = (from..to)
+        .filter_map(|position| state.history.get(&position).map(|entry| entry.clone()))
+        .collect();
+
+    Ok(ViewHistoryResponse {
+        total: state.history_end - state.history_start,
+        entries,
+    })
+}
+
+/// View the total CCD sent to the given receivers and in the given months.
+#[receive(
+    contract = "multisig_wallet",
+    name = "view_spending_stats",
+    parameter = "SpendingStatsParams",
+    return_value = "SpendingStatsResponse",
+    error = "Error"
+)]
+pub fn contract_receive_view_spending_stats<S: HasStateApi>(
+    ctx: &impl HasReceiveContext,
+    host: &impl HasHost<State<S>, StateApiType = S>,
+) -> Result<SpendingStatsResponse, Error> {
+    let params: SpendingStatsParams = ctx.parameter_cursor().get()?;
+    let state = host.state();
+
+    let per_receiver = params
+        .receivers
+        .into_iter()
+        .map(|receiver| {
+            let total = state
+                .spent_per_receiver
+                .get(&receiver)
+                .map_or(Amount::zero(), |total| *total);
+            (receiver, total)
+        })
+        .collect();
+    let per_month = params
+        .months
+        .into_iter()
+        .map(|month| {
+            let total = state
+                .spent_per_month
+                .get(&month)
+                .map_or(Amount::zero(), |total| *total);
+            (month, total)
+        })
+        .collect();
+
+    Ok(SpendingStatsResponse {
+        per_receiver,
+        per_month,
+    })
+}
+
+/// Replace the sender by a new address in the owners and in the supporters
+/// and rejectors of pending requests and proposals, so in-flight approvals
+/// are kept. Executed requests are not in `requests` anymore, so the history
+/// keeps the original address. A delegation moves to the new address, a
+/// registered signing key does not and has to be registered again.
+#[receive(
+    contract = "multisig_wallet",
+    name = "rotate_owner_key",
+    parameter = "Address",
+    mutable,
+    error = "Error"
+)]
+pub fn contract_receive_rotate_owner_key<S: HasStateApi>(
+    ctx: &impl HasReceiveContext,
+    host: &mut impl HasHost<State<S>, StateApiType = S>,
+) -> Result<(), Error> {
+    let sender = ctx.sender();
+    let owners = &host.state().owners;
+
+    ensure!(is_owner(sender, owners), Error::NotOwner);
+
+    let new_address: Address = ctx.parameter_cursor().get()?;
+    ensure!(!is_owner(new_address, owners), Error::AlreadyOwner);
+
+    let state = host.state_mut();
+    replace_address(&mut state.owners, sender, new_address);
+    for (_, mut request) in state.requests.iter_mut() {
+        replace_address(&mut request.supporters, sender, new_address);
+        replace_address(&mut request.rejectors, sender, new_address);
+    }
+    for (_, mut proposal) in state.proposals.iter_mut() {
+        replace_address(&mut proposal.supporters, sender, new_address);
+    }
+    if let Some(delegation) = state.delegations.remove_and_get(&sender) {
+        state.delegations.insert(new_address, delegation);
+    }
+    state.owner_keys.remove(&sender);
+
+    Ok(())
+}
+
+/// Register the public key the sender signs off-chain approvals with.
+#[receive(
+    contract = "multisig_wallet",
+    name = "register_owner_key",
+    parameter = "PublicKeyEd25519",
+    mutable,
+    error = "Error"
+)]
+pub fn contract_receive_register_owner_key<S: HasStateApi>(
+    ctx: &impl HasReceiveContext,
+    host: &mut impl HasHost<State<S>, StateApiType = S>,
+) -> Result<(), Error> {
+    let sender = ctx.sender();
+    let owners = &host.state().owners;
+
+    ensure!(is_owner(sender, owners), Error::NotOwner);
+
+    let public_key: PublicKeyEd25519 = ctx.parameter_cursor().get()?;
+    host.state_mut().owner_keys.insert(sender, public_key);
+
+    Ok(())
+}
+
+/// Submit a transfer request together with approvals the owners signed
+/// off-chain. Anyone can relay the signatures. If the approvals reach the
+/// threshold, there is no execution delay and the relayer has the `Executor`
+/// role, the transfer is made right away.
+#[receive(
+    contract = "multisig_wallet",
+    name = "submit_signed_transfer_request",
+    parameter = "SignedSubmitParams",
+    mutable,
+    enable_logger,
+    crypto_primitives,
+    error = "Error"
+)]
+pub fn contract_receive_submit_signed_transfer_request<S: HasStateApi>(
+    ctx: &impl HasReceiveContext,
+    host: &mut impl HasHost<State<S>, StateApiType = S>,
+    logger: &mut impl HasLogger,
+    crypto_primitives: &impl HasCryptoPrimitives,
+) -> Result<TransferRequestId, Error> {
+    ensure!(!host.state().paused, Error::ContractPaused);
+
+    let params: SignedSubmitParams = ctx.parameter_cursor().get()?;
+
+    ensure!(!params.signatures.is_empty(), Error::MissingSignatures);
+    ensure!(is_valid_memo(&params.request.memo), Error::MemoTooLong);
+    ensure_eq!(
+        params.nonce,
+        host.state().signature_nonce,
+        Error::NonceMismatch
+    );
+
+    let message = to_bytes(&SignedApprovalMessage {
+        chain_context: host.state().chain_context,
+        contract_address: ctx.self_address(),
+        nonce: params.nonce,
+        request: params.request.clone(),
+    });
+
+    let mut supporters = BTreeSet::new();
+    for OwnerSignature { owner, signature } in params.signatures {
+        ensure!(is_owner(owner, &host.state().owners), Error::NotOwner);
+        let public_key = *host
+            .state()
+            .owner_keys
+            .get(&owner)
+            .ok_or(Error::OwnerKeyNotRegistered)?;
+        ensure!(
+            crypto_primitives.verify_ed25519_signature(public_key, signature, &message),
+            Error::InvalidSignature
+        );
+        ensure!(supporters.insert(owner), Error::RequestAlreadySupported);
+    }
+
+    let now = ctx.metadata().slot_time();
+    let req_id = host.state().last_request_id + 1;
+
+    let mut new_request = TransferRequest {
+        status: RequestStatus::Pending,
+        kind: RequestKind::Standard,
+        transfer_amount: params.request.transfer_amount,
+        receiver: params.request.receiver.clone(),
+        supporters,
+        rejectors: BTreeSet::new(),
+        executable_at: None,
+        memo: params.request.memo,
+        threshold: request_threshold(host.state(), params.request.threshold),
+        expiry: params.request.expiry,
+    };
+    new_request.update_queue(now, host.state().execution_delay);
+    let executable_now = new_request
+        .executable_at
+        .map_or(false, |executable_at| now >= executable_at)
+        && has_role(host.state(), ctx.sender(), Role::Executor);
+
+    logger.log(&submit_event(req_id, &new_request))?;
+    insert_request(
+        host.state_mut(),
+        logger,
+        req_id,
+        new_request,
+        now,
+        crypto_primitives,
+    )?;
+    host.state_mut().signature_nonce += 1;
+
+    if executable_now {
+        execute_request(host, logger, req_id, ctx.sender(), now, crypto_primitives)?;
+    }
+
+    Ok(req_id)
+}
+
+/// Hash of a permit message as signed by the owner, following CIS-3: the
+/// message is prefixed with the signer and 8 zero bytes, as wallets do for
+/// account signatures. The message contains the address of the wallet and
+/// the nonce of the signer, so a permit cannot be replayed on this chain.
+///
+/// Unlike off-chain approvals, the chain context is not part of the signed
+/// data, since CIS-3 wallets sign the message as is. A permit could only be
+/// replayed on another chain by a wallet at the same contract address whose
+/// signer has the same nonce there.
+fn permit_message_hash(
+    signer: AccountAddress,
+    message: &PermitMessage,
+    crypto_primitives: &impl HasCryptoPrimitives,
+) -> [u8; 32] {
+    let mut data = signer.0.to_vec();
+    data.extend_from_slice(&[0u8; 8]);
+    data.extend_from_slice(&to_bytes(message));
+    crypto_primitives.hash_sha2_256(&data).0
+}
+
+/// Invoke `support_transfer_request` or `not_support_transfer_request` on
+/// behalf of an owner who signed the message off-chain, so that a relayer can
+/// pay for the transaction. See CIS-3.
+#[receive(
+    contract = "multisig_wallet",
+    name = "permit",
+    parameter = "PermitParam",
+    mutable,
+    enable_logger,
+    crypto_primitives,
+    error = "Error"
+)]
+pub fn contract_receive_permit<S: HasStateApi>(
+    ctx: &impl HasReceiveContext,
+    host: &mut impl HasHost<State<S>, StateApiType = S>,
+    logger: &mut impl HasLogger,
+    crypto_primitives: &impl HasCryptoPrimitives,
+) -> Result<(), Error> {
+    let param: PermitParam = ctx.parameter_cursor().get()?;
+    let signer = param.signer;
+    let message = param.message;
+
+    ensure!(
+        has_role(host.state(), Address::Account(signer), Role::Approver),
+        Error::NotOwner
+    );
+    ensure_eq!(
+        message.contract_address,
+        ctx.self_address(),
+        Error::WrongContract
+    );
+    let now = ctx.metadata().slot_time();
+    ensure!(message.timestamp > now, Error::PermitExpired);
+
+    let nonce = host.state().nonces.get(&signer).map_or(0, |nonce| *nonce);
+    ensure_eq!(message.nonce, nonce, Error::NonceMismatch);
+
+    let message_hash = permit_message_hash(signer, &message, crypto_primitives);
+    let valid_signature = host
+        .check_account_signature(signer, &param.signature, &message_hash)
+        .map_err(|_| Error::InvalidSignature)?;
+    ensure!(valid_signature, Error::InvalidSignature);
+
+    host.state_mut().nonces.insert(signer, nonce + 1);
+
+    let request_id: TransferRequestId = from_bytes(&message.payload)?;
+    let entry_point = message.entry_point.as_entrypoint_name();
+    if entry_point == SUPPORTS_PERMIT_ENTRYPOINTS[0] {
+        support_request(
+            host.state_mut(),
+            logger,
+            Address::Account(signer),
+            request_id,
+            now,
+        )?;
+    } else if entry_point == SUPPORTS_PERMIT_ENTRYPOINTS[1] {
+        withdraw_support(
+            host.state_mut(),
+            logger,
+            Address::Account(signer),
+            request_id,
+            now,
+        )?;
+    } else {
+        bail!(Error::UnsupportedPermitEntrypoint);
+    }
+
+    logger.log(&Event::Nonce(NonceEvent {
+        account: signer,
+        nonce,
+    }))?;
+
+    Ok(())
+}
+
+/// View the hash an account signs for a permit.
+#[receive(
+    contract = "multisig_wallet",
+    name = "viewMessageHash",
+    parameter = "PermitParam",
+    return_value = "[u8; 32]",
+    crypto_primitives,
+    error = "Error"
+)]
+pub fn contract_receive_view_message_hash<S: HasStateApi>(
+    ctx: &impl HasReceiveContext,
+    _host: &impl HasHost<State<S>, StateApiType = S>,
+    crypto_primitives: &impl HasCryptoPrimitives,
+) -> Result<[u8; 32], Error> {
+    let param: PermitParam = ctx.parameter_cursor().get()?;
 
-#[derive(Serialize, SchemaType, Clone)]
-pub struct TransferRequest {
-    pub transfer_amount: Amount,
-    pub target_account: AccountAddress,
-    pub supporters: BTreeSet<AccountAddress>,
+    Ok(permit_message_hash(
+        param.signer,
+        &param.message,
+        crypto_primitives,
+    ))
 }
 
-///smart contract state.
-#[derive(Serial, DeserialWithState)]
-#[concordium(state_parameter = "S")]
-pub struct State<S> {
-    /// Who is authorized to sig (must be non-empty)
-    pub owners: BTreeSet<AccountAddress>,
+/// Query which standards the wallet implements, see CIS-0.
+#[receive(
+    contract = "multisig_wallet",
+    name = "supports",
+    parameter = "SupportsQueryParams",
+    return_value = "SupportsQueryResponse",
+    error = "Error"
+)]
+pub fn contract_receive_supports<S: HasStateApi>(
+    ctx: &impl HasReceiveContext,
+    _host: &impl HasHost<State<S>, StateApiType = S>,
+) -> Result<SupportsQueryResponse, Error> {
+    let params: SupportsQueryParams = ctx.parameter_cursor().get()?;
 
-    ///The id assigned to last request
-    pub last_request_id: TransferRequestId,
+    let results = params
+        .queries
+        .iter()
+        .map(|standard| {
+            if SUPPORTED_STANDARDS.contains(&standard.id.as_str()) {
+                SupportResult::Support
+            } else {
+                SupportResult::NoSupport
+            }
+        })
+        .collect();
 
-    /// Requests which have not been dropped due to timing out or due to being
-    /// agreed to yet The request ID, the associated amount, when it times
-    /// out, who is making the transfer and which account owners support
-    /// this transfer
-    pub requests: StateMap<TransferRequestId, TransferRequest, S>,
+    Ok(SupportsQueryResponse { results })
 }
 
-#[derive(Serialize, SchemaType, Clone)]
-pub struct InitParams {
-    /// Who is authorized to sig (must be non-empty)
-    #[concordium(size_length = 1)]
-    pub owners: BTreeSet<AccountAddress>,
-}
+/// Query which entrypoints can be invoked with `permit`.
+#[receive(
+    contract = "multisig_wallet",
+    name = "supportsPermit",
+    parameter = "SupportsPermitQueryParams",
+    return_value = "SupportsQueryResponse",
+    error = "Error"
+)]
+pub fn contract_receive_supports_permit<S: HasStateApi>(
+    ctx: &impl HasReceiveContext,
+    _host: &impl HasHost<State<S>, StateApiType = S>,
+) -> Result<SupportsQueryResponse, Error> {
+    let params: SupportsPermitQueryParams = ctx.parameter_cursor().get()?;
 
-#[derive(Serialize, SchemaType, Clone)]
-pub struct SubmitParams {
-    pub transfer_amount: Amount,
-    pub target_account: AccountAddress,
+    let results = params
+        .queries
+        .iter()
+        .map(|entry_point| {
+            if SUPPORTS_PERMIT_ENTRYPOINTS.contains(&entry_point.as_entrypoint_name()) {
+                SupportResult::Support
+            } else {
+                SupportResult::NoSupport
+            }
+        })
+        .collect();
+
+    Ok(SupportsQueryResponse { results })
 }
 
-#[derive(Debug, PartialEq, Eq, Reject, Serial, SchemaType)]
-pub enum Error {
-    /// Failed parsing the parameter.
-    #[from(ParseError)]
-    ParseParams,
+/// Query the nonce the next permit of each account must be signed with.
+#[receive(
+    contract = "multisig_wallet",
+    name = "nonceOf",
+    parameter = "NonceOfQueryParams",
+    return_value = "NonceOfQueryResponse",
+    error = "Error"
+)]
+pub fn contract_receive_nonce_of<S: HasStateApi>(
+    ctx: &impl HasReceiveContext,
+    host: &impl HasHost<State<S>, StateApiType = S>,
+) -> Result<NonceOfQueryResponse, Error> {
+    let params: NonceOfQueryParams = ctx.parameter_cursor().get()?;
 
-    /// Not enough account owners.
-    InsufficientOwners,
-    /// Only account owners can interact with this contract.
-    NotOwner,
-    /// Sender cannot be a contract.
-    ContractSender,
-    /// Not enough available funds for the requested transfer.
-    InsufficientAvailableFunds,
-    /// Not such request funds.
-    RequestNotFound,
-    /// A request with this ID already exists.
-    RequestAlreadyExists,
-    /// Transfer amount or account is different from the request.
-    MismatchingRequestInformation,
-    /// You have already supported this transfer.
-    RequestAlreadySupported,
-    /// You have not already supported this transfer.
-    RequestAlreadyNotSupported,
-    /// All owners have not supported the request
-    RequestNotSupportedByAllOwners,
+    let results = params
+        .queries
+        .iter()
+        .map(|account| host.state().nonces.get(account).map_or(0, |nonce| *nonce))
+        .collect();
 
-    /// Invalid receiver when invoking a transfer.
-    InvokeTransferMissingAccount,
-    /// Insufficient funds when invoking a transfer.
-    InvokeTransferInsufficientFunds,
+    Ok(NonceOfQueryResponse { results })
 }
 
-/// Mapping errors related to transfer invocations to CustomContractError.
-impl From<TransferError> for Error {
-    fn from(te: TransferError) -> Self {
-        match te {
-            TransferError::AmountTooLarge => Self::InvokeTransferInsufficientFunds,
-            TransferError::MissingAccount => Self::InvokeTransferMissingAccount,
-        }
-    }
-}
+/// Transfer CCD without collecting approvals, as long as the total sent this
+/// way stays within the daily limit. The transfer is recorded in the history
+/// without a request id.
+#[receive(
+    contract = "multisig_wallet",
+    name = "quick_transfer",
+    parameter = "QuickTransferParams",
+    mutable,
+    enable_logger,
+    error = "Error"
+)]
+pub fn contract_receive_quick_transfer<S: HasStateApi>(
+    ctx: &impl HasReceiveContext,
+    host: &mut impl HasHost<State<S>, StateApiType = S>,
+    logger: &mut impl HasLogger,
+) -> Result<(), Error> {
+    ensure!(!host.state().paused, Error::ContractPaused);
 
-fn is_owner(account: Address, owners: &BTreeSet<AccountAddress>) -> bool {
-    owners.iter().any(|owner| account.matches_account(owner))
-}
+    let sender = ctx.sender();
+    let owners = &host.state().owners;
 
-// Contract implementation
-//--------------- contract functions ----------
-#[init(contract = "multisig_wallet", parameter = "InitParams", payable)]
-#[inline(always)]
-pub fn contract_init<S: HasStateApi>(
-    ctx: &impl HasInitContext,
-    state_builder: &mut StateBuilder<S>,
-    _amount: Amount,
-) -> Result<State<S>, Error> {
-    let init_params: InitParams = ctx.parameter_cursor().get()?;
-    let owners = init_params.owners;
+    ensure!(is_owner(sender, owners), Error::NotOwner);
+
+    let params: QuickTransferParams = ctx.parameter_cursor().get()?;
+    let now = ctx.metadata().slot_time();
+    let state = host.state_mut();
+
+    check_transfer_policy(
+        &state.transfer_policy,
+        Address::Account(params.target_account),
+        params.transfer_amount,
+    )?;
+
+    let window_expired = now
+        .duration_since(state.daily_window_start)
+        .map_or(false, |elapsed| elapsed >= DAILY_LIMIT_WINDOW);
+    if window_expired {
+        state.daily_window_start = now;
+        state.daily_spent = Amount::zero();
+    }
+
+    let remaining = state
+        .daily_limit
+        .micro_ccd
+        .saturating_sub(state.daily_spent.micro_ccd);
     ensure!(
-        owners.len() == TRANSFER_AGREEMENT_THRESHOLD,
-        Error::InsufficientOwners
+        params.transfer_amount.micro_ccd <= remaining,
+        Error::DailyLimitExceeded
+    );
+    state.daily_spent =
+        Amount::from_micro_ccd(state.daily_spent.micro_ccd + params.transfer_amount.micro_ccd);
+    record_spending(
+        state,
+        Address::Account(params.target_account),
+        params.transfer_amount,
+        now,
+    );
+    archive_transfer(
+        state,
+        None,
+        ExecutedTransfer {
+            executed_at: now,
+            executor: sender,
+            transfer_amount: params.transfer_amount,
+            receiver: Receiver::Account(params.target_account),
+            memo: None,
+        },
     );
 
-    let state = State {
-        owners,
-        last_request_id: 0,
-        requests: state_builder.new_map(),
-    };
+    host.invoke_transfer(&params.target_account, params.transfer_amount)?;
 
-    Ok(state)
-}
+    logger.log(&Event::QuickTransfer(QuickTransferEvent {
+        sender,
+        transfer_amount: params.transfer_amount,
+        target_account: params.target_account,
+    }))?;
 
-#[receive(contract = "multisig_wallet", name = "deposit", payable)]
-fn contract_receive_deposit<S: HasStateApi>(
-    _ctx: &impl HasReceiveContext,
-    _host: &impl HasHost<State<S>, StateApiType = S>,
-    _amount: Amount,
-) -> ReceiveResult<()> {
     Ok(())
 }
 
 #[receive(
     contract = "multisig_wallet",
-    name = "submit_transfer_request",
-    parameter = "SubmitParams",
+    name = "submit_governance_proposal",
+    parameter = "GovernanceAction",
     mutable,
     error = "Error"
 )]
-pub fn contract_receive_submit_transfer_request<S: HasStateApi>(
+pub fn contract_receive_submit_governance_proposal<S: HasStateApi>(
     ctx: &impl HasReceiveContext,
     host: &mut impl HasHost<State<S>, StateApiType = S>,
-) -> Result<TransferRequestId, Error> {
+) -> Result<GovernanceProposalId, Error> {
     let sender = ctx.sender();
     let owners = &host.state().owners;
 
     ensure!(is_owner(sender, owners), Error::NotOwner);
 
-    let sender_address = match sender {
-        Address::Contract(_) => bail!(Error::ContractSender),
-        Address::Account(account_address) => account_address,
-    };
+    let action: GovernanceAction = ctx.parameter_cursor().get()?;
 
-    let submit_params: SubmitParams = ctx.parameter_cursor().get()?;
+    Ok(submit_proposal(host.state_mut(), sender, action))
+}
 
-    let req_id = host.state().last_request_id + 1;
-    let transfer_amount = submit_params.transfer_amount;
-    let target_account = submit_params.target_account;
+#[receive(
+    contract = "multisig_wallet",
+    name = "support_governance_proposal",
+    parameter = "GovernanceProposalId",
+    mutable,
+    error = "Error"
+)]
+pub fn contract_receive_support_governance_proposal<S: HasStateApi>(
+    ctx: &impl HasReceiveContext,
+    host: &mut impl HasHost<State<S>, StateApiType = S>,
+) -> Result<(), Error> {
+    let sender = ctx.sender();
+    let owners = &host.state().owners;
 
-    let mut supporters = BTreeSet::new();
-    supporters.insert(sender_address);
+    ensure!(is_owner(sender, owners), Error::NotOwner);
 
-    let new_request = TransferRequest {
-        transfer_amount,
-        target_account,
-        supporters,
-    };
+    let proposal_id: GovernanceProposalId = ctx.parameter_cursor().get()?;
 
-    host.state_mut().requests.insert(req_id, new_request);
-    host.state_mut().last_request_id = req_id;
+    let mut matching_proposal = host
+        .state_mut()
+        .proposals
+        .entry(proposal_id)
+        .occupied_or(Error::ProposalNotFound)?;
 
-    Ok(req_id)
+    ensure!(
+        !matching_proposal.supporters.contains(&sender),
+        Error::ProposalAlreadySupported
+    );
+    matching_proposal.supporters.insert(sender);
+
+    Ok(())
 }
 
+/// Apply a governance proposal once it is supported by enough owners.
 #[receive(
     contract = "multisig_wallet",
-    name = "support_transfer_request",
-    parameter = "TransferRequestId",
+    name = "execute_governance_proposal",
+    parameter = "GovernanceProposalId",
     mutable,
     error = "Error"
 )]
-pub fn contract_receive_support_transfer_request<S: HasStateApi>(
+pub fn contract_receive_execute_governance_proposal<S: HasStateApi>(
     ctx: &impl HasReceiveContext,
     host: &mut impl HasHost<State<S>, StateApiType = S>,
 ) -> Result<(), Error> {
@@ -187,36 +2525,39 @@ pub fn contract_receive_support_transfer_request<S: HasStateApi>(
 
     ensure!(is_owner(sender, owners), Error::NotOwner);
 
-    let sender_address = match sender {
-        Address::Contract(_) => bail!(Error::ContractSender),
-        Address::Account(account_address) => account_address,
-    };
+    let proposal_id: GovernanceProposalId = ctx.parameter_cursor().get()?;
 
-    let request_id: TransferRequestId = ctx.parameter_cursor().get()?;
+    let action = take_approved_proposal(host.state_mut(), proposal_id)?;
+    apply_governance_action(host.state_mut(), action)
+}
 
-    let mut matching_request = host
-        .state_mut()
-        .requests
-        .entry(request_id)
-        .occupied_or(Error::RequestNotFound)?;
+/// Pause the wallet. Any single owner can do this, e.g. when a key is
+/// suspected to be compromised. Unpausing needs an `Unpause` governance
+/// proposal supported by the threshold of owners.
+#[receive(contract = "multisig_wallet", name = "pause", mutable, error = "Error")]
+pub fn contract_receive_pause<S: HasStateApi>(
+    ctx: &impl HasReceiveContext,
+    host: &mut impl HasHost<State<S>, StateApiType = S>,
+) -> Result<(), Error> {
+    let sender = ctx.sender();
+    let owners = &host.state().owners;
 
-    ensure!(
-        !matching_request.supporters.contains(&sender_address),
-        Error::RequestAlreadySupported
-    );
-    matching_request.supporters.insert(sender_address);
+    ensure!(is_owner(sender, owners), Error::NotOwner);
+
+    host.state_mut().paused = true;
 
     Ok(())
 }
 
+/// Unpause the wallet by executing an approved `Unpause` governance proposal.
 #[receive(
     contract = "multisig_wallet",
-    name = "not_support_transfer_request",
-    parameter = "TransferRequestId",
+    name = "unpause",
+    parameter = "GovernanceProposalId",
     mutable,
     error = "Error"
 )]
-pub fn contract_receive_not_support_transfer_request<S: HasStateApi>(
+pub fn contract_receive_unpause<S: HasStateApi>(
     ctx: &impl HasReceiveContext,
     host: &mut impl HasHost<State<S>, StateApiType = S>,
 ) -> Result<(), Error> {
@@ -225,36 +2566,60 @@ pub fn contract_receive_not_support_transfer_request<S: HasStateApi>(
 
     ensure!(is_owner(sender, owners), Error::NotOwner);
 
-    let sender_address = match sender {
-        Address::Contract(_) => bail!(Error::ContractSender),
-        Address::Account(account_address) => account_address,
-    };
+    let proposal_id: GovernanceProposalId = ctx.parameter_cursor().get()?;
 
-    let request_id: TransferRequestId = ctx.parameter_cursor().get()?;
+    let action = take_approved_proposal(host.state_mut(), proposal_id)?;
+    ensure_eq!(
+        action,
+        GovernanceAction::Unpause,
+        Error::WrongProposalAction
+    );
+    apply_governance_action(host.state_mut(), action)
+}
 
-    let mut matching_request = host
-        .state_mut()
-        .requests
-        .entry(request_id)
-        .occupied_or(Error::RequestNotFound)?;
+/// Propose a new approval threshold for the wallet. The proposal is supported
+/// with `support_governance_proposal` and applied with
+/// `execute_threshold_change` once every owner supports it.
+#[receive(
+    contract = "multisig_wallet",
+    name = "propose_threshold_change",
+    parameter = "u32",
+    mutable,
+    error = "Error"
+)]
+pub fn contract_receive_propose_threshold_change<S: HasStateApi>(
+    ctx: &impl HasReceiveContext,
+    host: &mut impl HasHost<State<S>, StateApiType = S>,
+) -> Result<GovernanceProposalId, Error> {
+    let sender = ctx.sender();
+    let owners = &host.state().owners;
 
+    ensure!(is_owner(sender, owners), Error::NotOwner);
+
+    let threshold: u32 = ctx.parameter_cursor().get()?;
     ensure!(
-        matching_request.supporters.contains(&sender_address),
-        Error::RequestAlreadyNotSupported
+        is_valid_threshold(threshold, owners),
+        Error::InvalidThreshold
     );
-    matching_request.supporters.remove(&sender_address);
 
-    Ok(())
+    Ok(submit_proposal(
+        host.state_mut(),
+        sender,
+        GovernanceAction::SetThreshold(threshold),
+    ))
 }
 
+/// Change the approval threshold by executing an approved `SetThreshold`
+/// governance proposal. Requests which are already submitted keep their
+/// threshold.
 #[receive(
     contract = "multisig_wallet",
-    name = "execute_transfer_request",
-    parameter = "TransferRequestId",
+    name = "execute_threshold_change",
+    parameter = "GovernanceProposalId",
     mutable,
     error = "Error"
 )]
-pub fn contract_receive_execute_transfer_request<S: HasStateApi>(
+pub fn contract_receive_execute_threshold_change<S: HasStateApi>(
     ctx: &impl HasReceiveContext,
     host: &mut impl HasHost<State<S>, StateApiType = S>,
 ) -> Result<(), Error> {
@@ -263,53 +2628,69 @@ pub fn contract_receive_execute_transfer_request<S: HasStateApi>(
 
     ensure!(is_owner(sender, owners), Error::NotOwner);
 
-    let request_id: TransferRequestId = ctx.parameter_cursor().get()?;
+    let proposal_id: GovernanceProposalId = ctx.parameter_cursor().get()?;
 
-    match host.state().requests.get(&request_id) {
-        None => Err(Error::RequestNotFound),
-        Some(matching_request) => {
-            ensure!(
-                !matching_request.supporters.len() == TRANSFER_AGREEMENT_THRESHOLD,
-                Error::RequestNotSupportedByAllOwners
-            );
-            let target_account = matching_request.target_account;
-            let transfer_amount = matching_request.transfer_amount;
+    let action = take_approved_proposal(host.state_mut(), proposal_id)?;
+    ensure!(
+        matches!(action, GovernanceAction::SetThreshold(_)),
+        Error::WrongProposalAction
+    );
+    apply_governance_action(host.state_mut(), action)
+}
+
+/// Propose new limits on the transfers owners can request. The proposal is
+/// supported with `support_governance_proposal` and applied with
+/// `execute_transfer_policy_change`.
+#[receive(
+    contract = "multisig_wallet",
+    name = "propose_transfer_policy_change",
+    parameter = "TransferPolicy",
+    mutable,
+    error = "Error"
+)]
+pub fn contract_receive_propose_transfer_policy_change<S: HasStateApi>(
+    ctx: &impl HasReceiveContext,
+    host: &mut impl HasHost<State<S>, StateApiType = S>,
+) -> Result<GovernanceProposalId, Error> {
+    let sender = ctx.sender();
+    let owners = &host.state().owners;
+
+    ensure!(is_owner(sender, owners), Error::NotOwner);
 
-            host.state_mut().requests.remove(&request_id);
-            host.invoke_transfer(&target_account, transfer_amount)?;
+    let policy: TransferPolicy = ctx.parameter_cursor().get()?;
 
-            Ok(())
-        }
-    }
+    Ok(submit_proposal(
+        host.state_mut(),
+        sender,
+        GovernanceAction::SetTransferPolicy(policy),
+    ))
 }
 
+/// Change the transfer limits by executing an approved `SetTransferPolicy`
+/// governance proposal. Requests which are already submitted are not checked
+/// again.
 #[receive(
     contract = "multisig_wallet",
-    name = "view_transfer_request",
-    parameter = "TransferRequestId",
+    name = "execute_transfer_policy_change",
+    parameter = "GovernanceProposalId",
     mutable,
-    return_value = "TransferRequest",
     error = "Error"
 )]
-pub fn contract_receive_view_transfer_request<S: HasStateApi>(
+pub fn contract_receive_execute_transfer_policy_change<S: HasStateApi>(
     ctx: &impl HasReceiveContext,
     host: &mut impl HasHost<State<S>, StateApiType = S>,
-) -> Result<TransferRequest, Error> {
+) -> Result<(), Error> {
     let sender = ctx.sender();
     let owners = &host.state().owners;
 
     ensure!(is_owner(sender, owners), Error::NotOwner);
 
-    let request_id: TransferRequestId = ctx.parameter_cursor().get()?;
+    let proposal_id: GovernanceProposalId = ctx.parameter_cursor().get()?;
 
-    match host.state().requests.get(&request_id) {
-        None => Err(Error::RequestNotFound),
-        Some(matching_request) => {
-            ensure!(
-                !matching_request.supporters.len() == TRANSFER_AGREEMENT_THRESHOLD,
-                Error::RequestNotSupportedByAllOwners
-            );
-            Ok(matching_request.clone())
-        }
-    }
+    let action = take_approved_proposal(host.state_mut(), proposal_id)?;
+    ensure!(
+        matches!(action, GovernanceAction::SetTransferPolicy(_)),
+        Error::WrongProposalAction
+    );
+    apply_governance_action(host.state_mut(), action)
 }
@@ -1,83 +1,1645 @@
-use concordium_smart_contract_testing::*;
-use multi_sig_contract::*;
+use concordium_smart_contract_testing::{AccountAccessStructure, AccountKeys, *};
+use concordium_std::{
+    collections::BTreeSet, to_bytes, HashSha2256, PublicKeyEd25519, Serial, SignatureEd25519,
+};
+use ed25519_dalek::{Keypair, PublicKey, SecretKey, Signer as _};
+use multi_sig_contract::{factory::*, *};
+use rand::{rngs::StdRng, SeedableRng};
 
-/// A test account.
+/// The test accounts.
 const ALICE: AccountAddress = AccountAddress([0u8; 32]);
 const ALICE_ADDR: Address = Address::Account(ALICE);
+const BOB: AccountAddress = AccountAddress([1u8; 32]);
+const BOB_ADDR: Address = Address::Account(BOB);
+const CHARLIE: AccountAddress = AccountAddress([2u8; 32]);
+const CHARLIE_ADDR: Address = Address::Account(CHARLIE);
+const DAVE: AccountAddress = AccountAddress([3u8; 32]);
+const DAVE_ADDR: Address = Address::Account(DAVE);
 
-/// The initial balance of the ALICE test account.
+/// The initial balance of the test accounts.
 const ACC_INITIAL_BALANCE: Amount = Amount::from_ccd(10_000);
 
 /// A [`Signer`] with one set of keys, used for signing transactions.
 const SIGNER: Signer = Signer::with_one_key();
 
-/// Test that invoking the `receive` endpoint with the `false` parameter
-/// succeeds in updating the contract.
+/// Test that a contract owner can submit a transfer request.
 #[test]
-fn test_throw_no_error() {
-    let (mut chain, init) = initialize();
+fn test_contract_owner_submits_request() {
+    let (mut chain, dao, wallet) = initialize();
 
-    // Update the contract via the `receive` entrypoint with the parameter `false`.
+    let request_id = submit_request(&mut chain, wallet, Address::Contract(dao))
+        .expect("Contract owner can submit a request");
+
+    assert_eq!(request_id, 1);
+}
+
+/// Test that a contract owner can support a transfer request and that its
+/// support is recorded.
+#[test]
+fn test_contract_owner_supports_request() {
+    let (mut chain, dao, wallet) = initialize();
+
+    let request_id = submit_request(&mut chain, wallet, ALICE_ADDR).expect("Submit succeeds");
+
+    invoke(&mut chain, wallet, Address::Contract(dao), "support_transfer_request", &request_id)
+        .expect("Contract owner can support a request");
+
+    // Supporting a second time fails, since the support is already recorded.
+    let update =
+        invoke(&mut chain, wallet, Address::Contract(dao), "support_transfer_request", &request_id)
+            .expect_err("Supporting twice fails");
+    let error: Error = update.parse_return_value().expect("Deserialize `Error`");
+    assert_eq!(error, Error::RequestAlreadySupported);
+}
+
+/// Test that a contract which is not an owner is rejected.
+#[test]
+fn test_non_owner_contract_rejected() {
+    let (mut chain, _dao, wallet) = initialize();
+
+    let update = submit_request(&mut chain, wallet, Address::Contract(wallet))
+        .expect_err("Non-owner contract cannot submit a request");
+    let error: Error = update.parse_return_value().expect("Deserialize `Error`");
+    assert_eq!(error, Error::NotOwner);
+}
+
+/// Test that a request is only executed once it has as many supporters as
+/// the threshold requires.
+#[test]
+fn test_execute_requires_threshold() {
+    // The DAO is a wallet owned by `Alice`, `Bob` and `Charlie`.
+    let (mut chain, dao, _) = initialize();
+    deposit(&mut chain, dao, Amount::from_ccd(10));
+
+    let request_id = submit_request(&mut chain, dao, ALICE_ADDR).expect("Submit succeeds");
+
+    for supporter in [BOB_ADDR, CHARLIE_ADDR] {
+        let update = invoke(&mut chain, dao, ALICE_ADDR, "execute_transfer_request", &request_id)
+            .expect_err("Execution fails below the threshold");
+        let error: Error = update.parse_return_value().expect("Deserialize `Error`");
+        assert_eq!(error, Error::RequestNotSupportedByAllOwners);
+
+        invoke(&mut chain, dao, supporter, "support_transfer_request", &request_id)
+            .expect("Owner can support a request");
+    }
+
+    invoke(&mut chain, dao, ALICE_ADDR, "execute_transfer_request", &request_id)
+        .expect("Execution succeeds at the threshold");
+    assert_eq!(chain.contract_balance(dao), Some(Amount::from_ccd(9)));
+}
+
+/// Test execution for every combination of supporters: it must succeed if and
+/// only if the number of supporters reaches the threshold.
+#[test]
+fn test_execute_for_all_supporter_sets() {
+    let owners = [ALICE_ADDR, BOB_ADDR, CHARLIE_ADDR];
+
+    for supporter_mask in 0u8..(1 << owners.len()) {
+        let (mut chain, dao, _) = initialize();
+        deposit(&mut chain, dao, Amount::from_ccd(10));
+
+        let request_id = submit_request(&mut chain, dao, ALICE_ADDR).expect("Submit succeeds");
+
+        // The submitter supports the request, so withdraw its support if it is
+        // not in the set.
+        if supporter_mask & 1 == 0 {
+            invoke(&mut chain, dao, ALICE_ADDR, "not_support_transfer_request", &request_id)
+                .expect("Owner can withdraw support");
+        }
+        for (i, owner) in owners.iter().enumerate().skip(1) {
+            if supporter_mask & (1 << i) != 0 {
+                invoke(&mut chain, dao, *owner, "support_transfer_request", &request_id)
+                    .expect("Owner can support a request");
+            }
+        }
+
+        let supporters = supporter_mask.count_ones() as usize;
+        let result = invoke(&mut chain, dao, ALICE_ADDR, "execute_transfer_request", &request_id);
+        if supporters >= TRANSFER_AGREEMENT_THRESHOLD {
+            result.expect("Execution succeeds at the threshold");
+            assert_eq!(chain.contract_balance(dao), Some(Amount::from_ccd(9)));
+        } else {
+            let error: Error = result
+                .expect_err("Execution fails below the threshold")
+                .parse_return_value()
+                .expect("Deserialize `Error`");
+            assert_eq!(error, Error::RequestNotSupportedByAllOwners);
+            assert_eq!(chain.contract_balance(dao), Some(Amount::from_ccd(10)));
+        }
+    }
+}
+
+/// Test that withdrawing support after reaching the threshold prevents the
+/// execution again.
+#[test]
+fn test_withdrawn_support_prevents_execution() {
+    let (mut chain, dao, _) = initialize();
+    deposit(&mut chain, dao, Amount::from_ccd(10));
+
+    let request_id = submit_request(&mut chain, dao, ALICE_ADDR).expect("Submit succeeds");
+    for supporter in [BOB_ADDR, CHARLIE_ADDR] {
+        invoke(&mut chain, dao, supporter, "support_transfer_request", &request_id)
+            .expect("Owner can support a request");
+    }
+    invoke(&mut chain, dao, BOB_ADDR, "not_support_transfer_request", &request_id)
+        .expect("Owner can withdraw support");
+
+    let update = invoke(&mut chain, dao, ALICE_ADDR, "execute_transfer_request", &request_id)
+        .expect_err("Execution fails below the threshold");
+    let error: Error = update.parse_return_value().expect("Deserialize `Error`");
+    assert_eq!(error, Error::RequestNotSupportedByAllOwners);
+}
+
+/// Test that executed transfers are added to the spending statistics.
+#[test]
+fn test_spending_stats_after_execution() {
+    let (mut chain, dao, _) = initialize();
+    deposit(&mut chain, dao, Amount::from_ccd(10));
+
+    let request_id = submit_request(&mut chain, dao, ALICE_ADDR).expect("Submit succeeds");
+    for supporter in [BOB_ADDR, CHARLIE_ADDR] {
+        invoke(&mut chain, dao, supporter, "support_transfer_request", &request_id)
+            .expect("Owner can support a request");
+    }
+    invoke(&mut chain, dao, ALICE_ADDR, "execute_transfer_request", &request_id)
+        .expect("Execution succeeds at the threshold");
+
+    // The test chain starts at the Unix epoch.
+    let month = CalendarMonth {
+        year:  1970,
+        month: 1,
+    };
+    let params = SpendingStatsParams {
+        receivers: vec![CHARLIE_ADDR, BOB_ADDR],
+        months:    vec![month],
+    };
+    let stats: SpendingStatsResponse =
+        invoke(&mut chain, dao, ALICE_ADDR, "view_spending_stats", &params)
+            .expect("View succeeds")
+            .parse_return_value()
+            .expect("Deserialize `SpendingStatsResponse`");
+    assert_eq!(stats.per_receiver, [
+        (CHARLIE_ADDR, Amount::from_ccd(1)),
+        (BOB_ADDR, Amount::zero())
+    ]);
+    assert_eq!(stats.per_month, [(month, Amount::from_ccd(1))]);
+}
+
+/// Test that rotating an owner's address keeps its support for pending
+/// requests.
+#[test]
+fn test_rotated_owner_keeps_support() {
+    // The DAO is a wallet owned by `Alice`, `Bob` and `Charlie`.
+    let (mut chain, dao, wallet) = initialize();
+    let new_bob = Address::Contract(wallet);
+
+    let request_id = submit_request(&mut chain, dao, ALICE_ADDR).expect("Submit succeeds");
+    invoke(&mut chain, dao, BOB_ADDR, "support_transfer_request", &request_id)
+        .expect("Owner can support a request");
+    invoke(&mut chain, dao, BOB_ADDR, "rotate_owner_key", &new_bob)
+        .expect("Owner can rotate its address");
+
+    let update = invoke(&mut chain, dao, BOB_ADDR, "support_transfer_request", &request_id)
+        .expect_err("Old address is no longer an owner");
+    let error: Error = update.parse_return_value().expect("Deserialize `Error`");
+    assert_eq!(error, Error::NotOwner);
+
+    let update = invoke(&mut chain, dao, new_bob, "support_transfer_request", &request_id)
+        .expect_err("Support moved to the new address");
+    let error: Error = update.parse_return_value().expect("Deserialize `Error`");
+    assert_eq!(error, Error::RequestAlreadySupported);
+}
+
+/// Test that requests to receivers outside the whitelist are rejected once
+/// the policy change is approved.
+#[test]
+fn test_transfer_policy_whitelist() {
+    let (mut chain, dao, _) = initialize();
+
+    let policy = TransferPolicy {
+        max_transfer_amount: None,
+        receiver_whitelist:  Some([BOB_ADDR].into_iter().collect()),
+    };
+    let proposal_id: GovernanceProposalId =
+        invoke(&mut chain, dao, ALICE_ADDR, "propose_transfer_policy_change", &policy)
+            .expect("Owner can propose a policy")
+            .parse_return_value()
+            .expect("Deserialize `GovernanceProposalId`");
+    for supporter in [BOB_ADDR, CHARLIE_ADDR] {
+        invoke(&mut chain, dao, supporter, "support_governance_proposal", &proposal_id)
+            .expect("Owner can support a proposal");
+    }
+    invoke(&mut chain, dao, ALICE_ADDR, "execute_transfer_policy_change", &proposal_id)
+        .expect("Approved policy change can be executed");
+
+    let update = submit_request(&mut chain, dao, ALICE_ADDR)
+        .expect_err("Receiver is not on the whitelist");
+    let error: Error = update.parse_return_value().expect("Deserialize `Error`");
+    assert_eq!(error, Error::ReceiverNotWhitelisted);
+}
+
+/// Test that the status of a request follows its lifecycle, that requests
+/// below the threshold can be viewed, and that executed requests are removed.
+#[test]
+fn test_request_status_lifecycle() {
+    let (mut chain, dao, _) = initialize();
+    deposit(&mut chain, dao, Amount::from_ccd(10));
+
+    let request_id = submit_request(&mut chain, dao, ALICE_ADDR).expect("Submit succeeds");
+    assert_eq!(view_status(&mut chain, dao, request_id), RequestStatus::Pending);
+
+    for supporter in [BOB_ADDR, CHARLIE_ADDR] {
+        invoke(&mut chain, dao, supporter, "support_transfer_request", &request_id)
+            .expect("Owner can support a request");
+    }
+    // Without an execution delay, the request can be executed right away.
+    assert_eq!(view_status(&mut chain, dao, request_id), RequestStatus::Approved);
+
+    invoke(&mut chain, dao, ALICE_ADDR, "execute_transfer_request", &request_id)
+        .expect("Execution succeeds at the threshold");
+
+    let update = invoke(&mut chain, dao, ALICE_ADDR, "view_transfer_request", &request_id)
+        .expect_err("Executed request is removed");
+    let error: Error = update.parse_return_value().expect("Deserialize `Error`");
+    assert_eq!(error, Error::RequestNotFound);
+
+    let update = invoke(&mut chain, dao, ALICE_ADDR, "cancel_transfer_request", &request_id)
+        .expect_err("Executed request cannot be cancelled");
+    let error: Error = update.parse_return_value().expect("Deserialize `Error`");
+    assert_eq!(error, Error::RequestNotFound);
+}
+
+/// Test that an approved request can only be executed once the execution
+/// delay has passed.
+#[test]
+fn test_execution_delay() {
+    let (mut chain, dao, _) =
+        initialize_with(|params| params.execution_delay = Duration::from_seconds(10));
+    deposit(&mut chain, dao, Amount::from_ccd(10));
+
+    let request_id = submit_request(&mut chain, dao, ALICE_ADDR).expect("Submit succeeds");
+    for supporter in [BOB_ADDR, CHARLIE_ADDR] {
+        invoke(&mut chain, dao, supporter, "support_transfer_request", &request_id)
+            .expect("Owner can support a request");
+    }
+    assert_eq!(view_status(&mut chain, dao, request_id), RequestStatus::Queued);
+
+    let update = invoke(&mut chain, dao, ALICE_ADDR, "execute_transfer_request", &request_id)
+        .expect_err("Execution delay has not passed");
+    let error: Error = update.parse_return_value().expect("Deserialize `Error`");
+    assert_eq!(error, Error::TimelockNotExpired);
+
+    chain.tick_block_time(Duration::from_seconds(10)).expect("Block time does not overflow");
+    assert_eq!(view_status(&mut chain, dao, request_id), RequestStatus::Approved);
+    invoke(&mut chain, dao, ALICE_ADDR, "execute_transfer_request", &request_id)
+        .expect("Execution succeeds after the delay");
+}
+
+/// Test that a veto blocks a request with enough support, until the owner
+/// withdraws it by supporting the request.
+#[test]
+fn test_veto() {
+    let (mut chain, dao, _) = initialize();
+    deposit(&mut chain, dao, Amount::from_ccd(10));
+    set_threshold(&mut chain, dao, 2);
+
+    let request_id = submit_request(&mut chain, dao, ALICE_ADDR).expect("Submit succeeds");
+    invoke(&mut chain, dao, BOB_ADDR, "support_transfer_request", &request_id)
+        .expect("Owner can support a request");
+    invoke(&mut chain, dao, CHARLIE_ADDR, "veto_transfer_request", &request_id)
+        .expect("Owner can veto a request");
+    assert_eq!(view_status(&mut chain, dao, request_id), RequestStatus::Pending);
+
+    let update = invoke(&mut chain, dao, ALICE_ADDR, "execute_transfer_request", &request_id)
+        .expect_err("Vetoed request cannot be executed");
+    let error: Error = update.parse_return_value().expect("Deserialize `Error`");
+    assert_eq!(error, Error::RequestVetoed);
+
+    invoke(&mut chain, dao, CHARLIE_ADDR, "support_transfer_request", &request_id)
+        .expect("Supporting withdraws the veto");
+    invoke(&mut chain, dao, ALICE_ADDR, "execute_transfer_request", &request_id)
+        .expect("Execution succeeds without vetoes");
+}
+
+/// Test that a cancelled request is removed, and that the same payment can be
+/// requested again.
+#[test]
+fn test_cancelled_request() {
+    let (mut chain, dao, _) = initialize();
+
+    let request_id = submit_request(&mut chain, dao, ALICE_ADDR).expect("Submit succeeds");
+    invoke(&mut chain, dao, BOB_ADDR, "cancel_transfer_request", &request_id)
+        .expect("Owner can cancel a request");
+
+    let update = invoke(&mut chain, dao, CHARLIE_ADDR, "support_transfer_request", &request_id)
+        .expect_err("Cancelled request cannot be supported");
+    let error: Error = update.parse_return_value().expect("Deserialize `Error`");
+    assert_eq!(error, Error::RequestNotFound);
+
+    submit_request(&mut chain, dao, ALICE_ADDR).expect("Same payment can be requested again");
+}
+
+/// Test that `state_stats` counts pending requests.
+#[test]
+fn test_state_stats() {
+    let (mut chain, dao, _) = initialize();
+
+    let request_id = submit_request(&mut chain, dao, ALICE_ADDR).expect("Submit succeeds");
+    invoke(&mut chain, dao, ALICE_ADDR, "cancel_transfer_request", &request_id)
+        .expect("Owner can cancel a request");
+    submit_request(&mut chain, dao, ALICE_ADDR).expect("Submit succeeds");
+
+    let stats: StateStats = invoke(&mut chain, dao, ALICE_ADDR, "state_stats", &())
+        .expect("View succeeds")
+        .parse_return_value()
+        .expect("Deserialize `StateStats`");
+    assert_eq!(stats.pending_requests, 1);
+    assert_eq!(stats.max_pending_requests, 10);
+    assert_eq!(stats.history, 0);
+    assert_eq!(stats.owners, 3);
+}
+
+/// Test that the number of pending requests is bounded, and that an expired
+/// request is evicted to make room for a new one.
+#[test]
+fn test_pending_requests_limit() {
+    let (mut chain, dao, _) = initialize_with(|params| params.max_pending_requests = 1);
+
+    let expiring = SubmitParams {
+        expiry: Some(Timestamp::from_timestamp_millis(1_000)),
+        ..payment()
+    };
+    let request_id = submit(&mut chain, dao, ALICE_ADDR, &expiring).expect("Submit succeeds");
+
+    let update = submit_request(&mut chain, dao, ALICE_ADDR)
+        .expect_err("Limit is reached and no request has expired");
+    let error: Error = update.parse_return_value().expect("Deserialize `Error`");
+    assert_eq!(error, Error::TooManyPendingRequests);
+
+    chain.tick_block_time(Duration::from_seconds(1)).expect("Block time does not overflow");
+    submit_request(&mut chain, dao, ALICE_ADDR).expect("Expired request is evicted");
+
+    let update = invoke(&mut chain, dao, ALICE_ADDR, "view_transfer_request", &request_id)
+        .expect_err("Evicted request is removed");
+    let error: Error = update.parse_return_value().expect("Deserialize `Error`");
+    assert_eq!(error, Error::RequestNotFound);
+}
+
+/// Test that the factory registers wallets by owner and rejects other
+/// contracts.
+#[test]
+fn test_factory_registers_wallets() {
+    let (mut chain, dao, wallet) = initialize();
+    let factory = init_factory(&mut chain, wallet);
+
+    for contract in [dao, wallet] {
+        invoke_factory(&mut chain, factory, "register_wallet", &contract)
+            .expect("Wallet can be registered");
+    }
+
+    let update = invoke_factory(&mut chain, factory, "register_wallet", &factory)
+        .expect_err("Factory is not a wallet");
+    let error: FactoryError = update.parse_return_value().expect("Deserialize `FactoryError`");
+    assert_eq!(error, FactoryError::NotAWallet);
+
+    assert_eq!(wallets_of_owner(&mut chain, factory, CHARLIE_ADDR), [dao]);
+    assert_eq!(wallets_of_owner(&mut chain, factory, Address::Contract(dao)), [wallet]);
+}
+
+/// Test that refreshing a wallet indexes it under its current owners.
+#[test]
+fn test_factory_refreshes_wallets() {
+    let (mut chain, dao, wallet) = initialize();
+    let factory = init_factory(&mut chain, wallet);
+    for contract in [dao, wallet] {
+        invoke_factory(&mut chain, factory, "register_wallet", &contract)
+            .expect("Wallet can be registered");
+    }
+
+    invoke(&mut chain, dao, ALICE_ADDR, "rotate_owner_key", &DAVE_ADDR)
+        .expect("Owner can rotate their key");
+    assert!(wallets_of_owner(&mut chain, factory, DAVE_ADDR).is_empty());
+
+    invoke_factory(&mut chain, factory, "refresh_wallet", &dao).expect("Wallet can be refreshed");
+    assert_eq!(wallets_of_owner(&mut chain, factory, DAVE_ADDR), [dao]);
+    assert_eq!(wallets_of_owner(&mut chain, factory, ALICE_ADDR), [wallet]);
+
+    let update = invoke_factory(&mut chain, factory, "refresh_wallet", &factory)
+        .expect_err("Factory is not registered");
+    let error: FactoryError = update.parse_return_value().expect("Deserialize `FactoryError`");
+    assert_eq!(error, FactoryError::WalletNotRegistered);
+}
+
+/// Helper method for initializing a factory from the module of the wallet.
+fn init_factory(chain: &mut Chain, wallet: ContractAddress) -> ContractAddress {
+    let module_reference = chain.get_contract(wallet).expect("Wallet exists").module_reference;
     chain
-        .contract_update(SIGNER, ALICE, ALICE_ADDR, Energy::from(10_000), UpdateContractPayload {
-            address:      init.contract_address,
-            amount:       Amount::zero(),
-            receive_name: OwnedReceiveName::new_unchecked("multi_sig_contract.receive".to_string()),
-            message:      OwnedParameter::from_serial(&false)
-                .expect("Parameter within size bounds"),
+        .contract_init(SIGNER, ALICE, Energy::from(100_000), InitContractPayload {
+            amount:    Amount::zero(),
+            mod_ref:   module_reference,
+            init_name: OwnedContractName::new_unchecked("init_multisig_factory".to_string()),
+            param:     OwnedParameter::empty(),
         })
-        .expect("Update succeeds with `false` as input.");
+        .expect("Initializing factory")
+        .contract_address
+}
+
+/// Helper method for viewing the registered wallets of an owner.
+fn wallets_of_owner(
+    chain: &mut Chain,
+    factory: ContractAddress,
+    owner: Address,
+) -> Vec<ContractAddress> {
+    invoke_factory(chain, factory, "view_wallets_of_owner", &owner)
+        .expect("View succeeds")
+        .parse_return_value()
+        .expect("Deserialize wallets")
+}
+
+/// Helper method for invoking an entrypoint of the factory as `ALICE`.
+fn invoke_factory<P: Serial>(
+    chain: &mut Chain,
+    factory: ContractAddress,
+    entrypoint: &str,
+    parameter: &P,
+) -> Result<ContractInvokeSuccess, ContractInvokeError> {
+    chain.contract_update(SIGNER, ALICE, ALICE_ADDR, Energy::from(100_000), UpdateContractPayload {
+        address:      factory,
+        amount:       Amount::zero(),
+        receive_name: OwnedReceiveName::new_unchecked(format!("multisig_factory.{}", entrypoint)),
+        message:      OwnedParameter::from_serial(parameter).expect("Parameter within size bounds"),
+    })
+}
+
+/// Test that a threshold change needs every owner, even once the threshold is
+/// lowered, while other proposals only need the threshold.
+#[test]
+fn test_threshold_change_needs_all_owners() {
+    let (mut chain, dao, _) = initialize();
+    set_threshold(&mut chain, dao, 2);
+
+    // `Alice` and `Bob` reach the threshold, but not every owner.
+    let proposal_id: GovernanceProposalId =
+        invoke(&mut chain, dao, ALICE_ADDR, "propose_threshold_change", &1u32)
+            .expect("Owner can propose a threshold")
+            .parse_return_value()
+            .expect("Deserialize `GovernanceProposalId`");
+    support_proposal(&mut chain, dao, proposal_id, &[BOB_ADDR]);
+    let update = invoke(&mut chain, dao, ALICE_ADDR, "execute_threshold_change", &proposal_id)
+        .expect_err("Threshold change needs every owner");
+    let error: Error = update.parse_return_value().expect("Deserialize `Error`");
+    assert_eq!(error, Error::ProposalNotSupportedByEnoughOwners);
+
+    let action = GovernanceAction::SetDailyLimit(Amount::from_ccd(1));
+    let proposal_id = propose(&mut chain, dao, &action);
+    support_proposal(&mut chain, dao, proposal_id, &[BOB_ADDR]);
+    invoke(&mut chain, dao, ALICE_ADDR, "execute_governance_proposal", &proposal_id)
+        .expect("Other proposals need the threshold");
+}
+
+/// Test that quick transfers are limited per daily window and recorded in the
+/// history.
+#[test]
+fn test_quick_transfer_daily_limit() {
+    let (mut chain, dao, _) = initialize();
+    deposit(&mut chain, dao, Amount::from_ccd(200));
+    let params = QuickTransferParams {
+        transfer_amount: Amount::from_ccd(60),
+        target_account:  CHARLIE,
+    };
+
+    invoke(&mut chain, dao, ALICE_ADDR, "quick_transfer", &params)
+        .expect("Transfer within the daily limit succeeds");
+    let update = invoke(&mut chain, dao, BOB_ADDR, "quick_transfer", &params)
+        .expect_err("Transfer exceeds the daily limit");
+    let error: Error = update.parse_return_value().expect("Deserialize `Error`");
+    assert_eq!(error, Error::DailyLimitExceeded);
+
+    chain.tick_block_time(DAILY_LIMIT_WINDOW).expect("Block time does not overflow");
+    invoke(&mut chain, dao, BOB_ADDR, "quick_transfer", &params)
+        .expect("Daily limit resets with the window");
+
+    let history = view_history(&mut chain, dao);
+    assert_eq!(history.total, 2);
+    let executors: Vec<_> = history
+        .entries
+        .iter()
+        .map(|entry| (entry.request_id, entry.transfer.executor))
+        .collect();
+    assert_eq!(executors, [(None, ALICE_ADDR), (None, BOB_ADDR)]);
 }
 
-/// Test that invoking the `receive` endpoint with the `true` parameter
-/// results in the `YourError` being thrown.
+/// Test that an owner who paused the wallet cannot block unpausing by never
+/// supporting the proposal.
 #[test]
-fn test_throw_error() {
-    let (mut chain, init) = initialize();
+fn test_unpause_needs_threshold() {
+    let (mut chain, dao, _) = initialize();
+    set_threshold(&mut chain, dao, 2);
 
-    // Update the contract via the `receive` entrypoint with the parameter `true`.
-    let update = chain
-        .contract_update(SIGNER, ALICE, ALICE_ADDR, Energy::from(10_000), UpdateContractPayload {
-            address:      init.contract_address,
-            amount:       Amount::zero(),
-            receive_name: OwnedReceiveName::new_unchecked("multi_sig_contract.receive".to_string()),
-            message:      OwnedParameter::from_serial(&true).expect("Parameter within size bounds"),
+    invoke(&mut chain, dao, CHARLIE_ADDR, "pause", &()).expect("Owner can pause");
+    let proposal_id = propose(&mut chain, dao, &GovernanceAction::Unpause);
+    support_proposal(&mut chain, dao, proposal_id, &[BOB_ADDR]);
+    invoke(&mut chain, dao, ALICE_ADDR, "unpause", &proposal_id)
+        .expect("Threshold of owners can unpause");
+
+    submit_request(&mut chain, dao, ALICE_ADDR).expect("Wallet is unpaused");
+}
+
+/// Test that a transfer request with a signature from every owner is executed
+/// right away when relayed by an executor, and only queued otherwise.
+#[test]
+fn test_signed_request() {
+    let (mut chain, dao, _) = initialize();
+    deposit(&mut chain, dao, Amount::from_ccd(10));
+    register_owner_keys(&mut chain, dao, &[ALICE_ADDR, BOB_ADDR, CHARLIE_ADDR]);
+
+    let signatures = sign_approvals(dao, 0, &[ALICE_ADDR, BOB_ADDR, CHARLIE_ADDR]);
+    let request_id = submit_signed(&mut chain, dao, DAVE_ADDR, 0, signatures)
+        .expect("Anyone can relay signatures");
+    assert_eq!(view_status(&mut chain, dao, request_id), RequestStatus::Approved);
+
+    let mut request = payment();
+    request.memo = Some("second".to_string());
+    let params = SignedSubmitParams {
+        signatures: [ALICE_ADDR, BOB_ADDR, CHARLIE_ADDR]
+            .iter()
+            .map(|owner| sign_approval(*owner, dao, 1, &request))
+            .collect(),
+        request,
+        nonce: 1,
+    };
+    let request_id: TransferRequestId =
+        invoke(&mut chain, dao, ALICE_ADDR, "submit_signed_transfer_request", &params)
+            .expect("Executor can relay signatures")
+            .parse_return_value()
+            .expect("Deserialize `TransferRequestId`");
+    let update = invoke(&mut chain, dao, ALICE_ADDR, "view_transfer_request", &request_id)
+        .expect_err("Request is executed right away");
+    let error: Error = update.parse_return_value().expect("Deserialize `Error`");
+    assert_eq!(error, Error::RequestNotFound);
+}
+
+/// Test that invalid signatures, unregistered keys, duplicate owners and
+/// reused nonces are rejected.
+#[test]
+fn test_signed_request_rejections() {
+    let (mut chain, dao, _) = initialize();
+    register_owner_keys(&mut chain, dao, &[ALICE_ADDR, BOB_ADDR]);
+
+    let mut signatures = sign_approvals(dao, 0, &[ALICE_ADDR]);
+    signatures.extend(sign_approvals(dao, 1, &[BOB_ADDR]));
+    let error = submit_signed(&mut chain, dao, ALICE_ADDR, 0, signatures)
+        .expect_err("Signature for another nonce is invalid");
+    assert_eq!(error, Error::InvalidSignature);
+
+    let signatures = sign_approvals(dao, 0, &[ALICE_ADDR, CHARLIE_ADDR]);
+    let error = submit_signed(&mut chain, dao, ALICE_ADDR, 0, signatures)
+        .expect_err("Owner without a registered key");
+    assert_eq!(error, Error::OwnerKeyNotRegistered);
+
+    let signatures = sign_approvals(dao, 0, &[ALICE_ADDR, ALICE_ADDR]);
+    let error = submit_signed(&mut chain, dao, ALICE_ADDR, 0, signatures)
+        .expect_err("Owner signed twice");
+    assert_eq!(error, Error::RequestAlreadySupported);
+
+    let signatures = sign_approvals(dao, 0, &[ALICE_ADDR, BOB_ADDR]);
+    submit_signed(&mut chain, dao, ALICE_ADDR, 0, signatures.clone())
+        .expect("Valid signatures are accepted");
+    let error = submit_signed(&mut chain, dao, ALICE_ADDR, 0, signatures)
+        .expect_err("Nonce was used already");
+    assert_eq!(error, Error::NonceMismatch);
+}
+
+/// Test that an owner can support a request with a permit relayed by someone
+/// else, and that the wallet reports CIS-3.
+#[test]
+fn test_permit() {
+    let (mut chain, dao, _) = initialize();
+    let keys = create_account_with_keys(&mut chain, ALICE);
+
+    let request_id = submit_request(&mut chain, dao, BOB_ADDR).expect("Submit succeeds");
+    let message = permit_message(dao, 0, "support_transfer_request", request_id);
+    permit(&mut chain, dao, &keys, message).expect("Valid permit succeeds");
+
+    let nonces: NonceOfQueryResponse =
+        invoke(&mut chain, dao, DAVE_ADDR, "nonceOf", &NonceOfQueryParams {
+            queries: vec![ALICE],
         })
-        .expect_err("Update fails with `true` as input.");
+        .expect("View succeeds")
+        .parse_return_value()
+        .expect("Deserialize `NonceOfQueryResponse`");
+    assert_eq!(nonces.results, [1]);
+    invoke(&mut chain, dao, CHARLIE_ADDR, "support_transfer_request", &request_id)
+        .expect("Owner can support a request");
+    assert_eq!(view_status(&mut chain, dao, request_id), RequestStatus::Approved);
 
-    // Check that the contract returned `YourError`.
+    let standards = ["CIS-0", "CIS-2", "CIS-3"]
+        .iter()
+        .map(|id| StandardIdentifierOwned {
+            id: id.to_string(),
+        })
+        .collect();
+    let supports: SupportsQueryResponse =
+        invoke(&mut chain, dao, DAVE_ADDR, "supports", &SupportsQueryParams {
+            queries: standards,
+        })
+        .expect("View succeeds")
+        .parse_return_value()
+        .expect("Deserialize `SupportsQueryResponse`");
+    assert_eq!(supports.results, [
+        SupportResult::Support,
+        SupportResult::NoSupport,
+        SupportResult::Support
+    ]);
+}
+
+/// Test that permits with a wrong nonce, for another contract, expired or for
+/// an unsupported entrypoint are rejected.
+#[test]
+fn test_permit_rejections() {
+    let (mut chain, dao, wallet) = initialize();
+    let keys = create_account_with_keys(&mut chain, ALICE);
+    chain.tick_block_time(Duration::from_seconds(10)).expect("Block time does not overflow");
+    let request_id = submit_request(&mut chain, dao, BOB_ADDR).expect("Submit succeeds");
+
+    let message = permit_message(dao, 1, "support_transfer_request", request_id);
+    let error = permit(&mut chain, dao, &keys, message).expect_err("Nonce does not match");
+    assert_eq!(error, Error::NonceMismatch);
+
+    let message = permit_message(wallet, 0, "support_transfer_request", request_id);
+    let error = permit(&mut chain, dao, &keys, message).expect_err("Permit is for another wallet");
+    assert_eq!(error, Error::WrongContract);
+
+    let mut message = permit_message(dao, 0, "support_transfer_request", request_id);
+    message.timestamp = Timestamp::from_timestamp_millis(10_000);
+    let error = permit(&mut chain, dao, &keys, message).expect_err("Permit has expired");
+    assert_eq!(error, Error::PermitExpired);
+
+    let message = permit_message(dao, 0, "execute_transfer_request", request_id);
+    let error = permit(&mut chain, dao, &keys, message).expect_err("Entrypoint is not supported");
+    assert_eq!(error, Error::UnsupportedPermitEntrypoint);
+}
+
+/// Test that a depositor has one pending refund at a time, bounded by the
+/// deposits, and that executing a refund pays out and reduces the deposits.
+#[test]
+fn test_refunds() {
+    let (mut chain, dao, _) = initialize();
+    deposit_from(&mut chain, dao, DAVE, Amount::from_ccd(10));
+
+    let update = request_refund(&mut chain, dao, Amount::zero()).expect_err("Zero refund");
+    assert_eq!(update, Error::InvalidAmount);
+
+    let update = request_refund(&mut chain, dao, Amount::from_ccd(11))
+        .expect_err("Refund exceeds the deposits");
+    assert_eq!(update, Error::InsufficientDeposit);
+    let refund_id =
+        request_refund(&mut chain, dao, Amount::from_ccd(6)).expect("Refund within deposits");
+    let update = request_refund(&mut chain, dao, Amount::from_ccd(1))
+        .expect_err("Refund is already pending");
+    assert_eq!(update, Error::RefundAlreadyPending);
+
+    let balance_before = chain.account_balance_available(DAVE).expect("Account exists");
+    for supporter in [ALICE_ADDR, BOB_ADDR, CHARLIE_ADDR] {
+        invoke(&mut chain, dao, supporter, "support_transfer_request", &refund_id)
+            .expect("Owner can support a refund");
+    }
+    invoke(&mut chain, dao, ALICE_ADDR, "execute_transfer_request", &refund_id)
+        .expect("Refund is executed");
+    assert_eq!(
+        chain.account_balance_available(DAVE),
+        Some(balance_before + Amount::from_ccd(6))
+    );
+
+    // 4 CCD are left.
+    let update = request_refund(&mut chain, dao, Amount::from_ccd(5))
+        .expect_err("Refund exceeds the deposits");
+    assert_eq!(update, Error::InsufficientDeposit);
+    let cancelled_id =
+        request_refund(&mut chain, dao, Amount::from_ccd(4)).expect("Refund within deposits");
+
+    invoke(&mut chain, dao, ALICE_ADDR, "cancel_transfer_request", &cancelled_id)
+        .expect("Owner can cancel a refund");
+    request_refund(&mut chain, dao, Amount::from_ccd(4))
+        .expect("Cancelled refund is no longer pending");
+}
+
+/// Test that a wallet cannot be initialized without room for requests.
+#[test]
+fn test_init_rejects_zero_pending_limit() {
+    let (mut chain, _, wallet) = initialize();
+    let params = InitParams {
+        owners: [ALICE_ADDR, BOB_ADDR, CHARLIE_ADDR].into_iter().collect(),
+        execution_delay: Duration::from_millis(0),
+        daily_limit: Amount::from_ccd(100),
+        chain_context: HashSha2256([0u8; 32]),
+        history_retention: None,
+        max_pending_requests: 0,
+    };
+    let module_reference = chain.get_contract(wallet).expect("Wallet exists").module_reference;
+    chain
+        .contract_init(SIGNER, ALICE, Energy::from(100_000), InitContractPayload {
+            amount:    Amount::zero(),
+            mod_ref:   module_reference,
+            init_name: OwnedContractName::new_unchecked("init_multisig_wallet".to_string()),
+            param:     OwnedParameter::from_serial(&params).expect("Parameter within size bounds"),
+        })
+        .expect_err("Wallet needs room for requests");
+}
+
+/// Test that pending refunds do not count towards the maximum of pending
+/// requests, so depositors cannot block the owners.
+#[test]
+fn test_refunds_outside_pending_limit() {
+    let (mut chain, dao, _) = initialize_with(|params| params.max_pending_requests = 1);
+    deposit_from(&mut chain, dao, DAVE, Amount::from_ccd(10));
+
+    request_refund(&mut chain, dao, Amount::from_ccd(1)).expect("Refund within deposits");
+    submit_request(&mut chain, dao, ALICE_ADDR).expect("Refund does not take the slot");
+
+    let stats: StateStats = invoke(&mut chain, dao, ALICE_ADDR, "state_stats", &())
+        .expect("View succeeds")
+        .parse_return_value()
+        .expect("Deserialize `StateStats`");
+    assert_eq!(stats.pending_requests, 1);
+    assert_eq!(stats.pending_refunds, 1);
+}
+
+/// Test that the payments of an approved recurring request can be triggered
+/// once due, until no occurrences are left, and are recorded in the history.
+#[test]
+fn test_recurring_payment() {
+    let (mut chain, dao, _) = initialize();
+    deposit(&mut chain, dao, Amount::from_ccd(10));
+
+    let schedule_id = activate_schedule(&mut chain, dao, RecurringSchedule {
+        interval:    Duration::from_seconds(10),
+        occurrences: 2,
+    });
+    assert_eq!(view_history(&mut chain, dao).total, 0);
+
+    invoke(&mut chain, dao, DAVE_ADDR, "trigger_scheduled_payment", &schedule_id)
+        .expect("First payment is due right away");
+    let update = invoke(&mut chain, dao, DAVE_ADDR, "trigger_scheduled_payment", &schedule_id)
+        .expect_err("Next payment is not due yet");
+    let error: Error = update.parse_return_value().expect("Deserialize `Error`");
+    assert_eq!(error, Error::PaymentNotDue);
+
+    chain.tick_block_time(Duration::from_seconds(10)).expect("Block time does not overflow");
+    invoke(&mut chain, dao, DAVE_ADDR, "trigger_scheduled_payment", &schedule_id)
+        .expect("Second payment is due after the interval");
+    assert_eq!(chain.contract_balance(dao), Some(Amount::from_ccd(8)));
+
+    chain.tick_block_time(Duration::from_seconds(10)).expect("Block time does not overflow");
+    let update = invoke(&mut chain, dao, DAVE_ADDR, "trigger_scheduled_payment", &schedule_id)
+        .expect_err("No occurrences are left");
+    let error: Error = update.parse_return_value().expect("Deserialize `Error`");
+    assert_eq!(error, Error::ScheduledPaymentNotFound);
+
+    let history = view_history(&mut chain, dao);
+    assert_eq!(history.total, 2);
+    assert!(history.entries.iter().all(|entry| entry.request_id == Some(schedule_id)));
+}
+
+/// Test that schedules without an interval or occurrences are rejected, and
+/// that an owner can stop an active schedule.
+#[test]
+fn test_recurring_payment_cancel() {
+    let (mut chain, dao, _) = initialize();
+
+    for schedule in [
+        RecurringSchedule {
+            interval:    Duration::from_millis(0),
+            occurrences: 1,
+        },
+        RecurringSchedule {
+            interval:    Duration::from_seconds(10),
+            occurrences: 0,
+        },
+    ] {
+        let update = submit_recurring(&mut chain, dao, schedule)
+            .expect_err("Schedule is invalid");
+        let error: Error = update.parse_return_value().expect("Deserialize `Error`");
+        assert_eq!(error, Error::InvalidSchedule);
+    }
+
+    let schedule_id = activate_schedule(&mut chain, dao, RecurringSchedule {
+        interval:    Duration::from_seconds(10),
+        occurrences: 5,
+    });
+    let update = invoke(&mut chain, dao, DAVE_ADDR, "cancel_scheduled_payment", &schedule_id)
+        .expect_err("Only owners can stop a schedule");
+    let error: Error = update.parse_return_value().expect("Deserialize `Error`");
+    assert_eq!(error, Error::NotOwner);
+
+    invoke(&mut chain, dao, BOB_ADDR, "cancel_scheduled_payment", &schedule_id)
+        .expect("Owner can stop a schedule");
+    let update = invoke(&mut chain, dao, DAVE_ADDR, "trigger_scheduled_payment", &schedule_id)
+        .expect_err("Schedule was stopped");
+    let error: Error = update.parse_return_value().expect("Deserialize `Error`");
+    assert_eq!(error, Error::ScheduledPaymentNotFound);
+}
+
+/// Test that a paused wallet rejects submitting, supporting and executing
+/// requests, until an approved `Unpause` proposal passes.
+#[test]
+fn test_pause() {
+    let (mut chain, dao, _) = initialize();
+    deposit(&mut chain, dao, Amount::from_ccd(10));
+
+    let approved_id = submit_request(&mut chain, dao, ALICE_ADDR).expect("Submit succeeds");
+    for supporter in [BOB_ADDR, CHARLIE_ADDR] {
+        invoke(&mut chain, dao, supporter, "support_transfer_request", &approved_id)
+            .expect("Owner can support a request");
+    }
+    let pending_id = submit(&mut chain, dao, ALICE_ADDR, &SubmitParams {
+        memo: Some("pending".to_string()),
+        ..payment()
+    })
+    .expect("Submit succeeds");
+
+    invoke(&mut chain, dao, CHARLIE_ADDR, "pause", &()).expect("Owner can pause");
+
+    let update = submit_request(&mut chain, dao, ALICE_ADDR).expect_err("Wallet is paused");
+    let error: Error = update.parse_return_value().expect("Deserialize `Error`");
+    assert_eq!(error, Error::ContractPaused);
+    let update = invoke(&mut chain, dao, BOB_ADDR, "support_transfer_request", &pending_id)
+        .expect_err("Wallet is paused");
+    let error: Error = update.parse_return_value().expect("Deserialize `Error`");
+    assert_eq!(error, Error::ContractPaused);
+    let update = invoke(&mut chain, dao, ALICE_ADDR, "execute_transfer_request", &approved_id)
+        .expect_err("Wallet is paused");
+    let error: Error = update.parse_return_value().expect("Deserialize `Error`");
+    assert_eq!(error, Error::ContractPaused);
+
+    let action = GovernanceAction::SetDailyLimit(Amount::from_ccd(1));
+    let proposal_id = propose(&mut chain, dao, &action);
+    support_proposal(&mut chain, dao, proposal_id, &[BOB_ADDR, CHARLIE_ADDR]);
+    let update = invoke(&mut chain, dao, ALICE_ADDR, "unpause", &proposal_id)
+        .expect_err("Proposal is for another action");
+    let error: Error = update.parse_return_value().expect("Deserialize `Error`");
+    assert_eq!(error, Error::WrongProposalAction);
+
+    let proposal_id = propose(&mut chain, dao, &GovernanceAction::Unpause);
+    support_proposal(&mut chain, dao, proposal_id, &[BOB_ADDR, CHARLIE_ADDR]);
+    invoke(&mut chain, dao, ALICE_ADDR, "unpause", &proposal_id)
+        .expect("Approved proposal unpauses the wallet");
+    invoke(&mut chain, dao, ALICE_ADDR, "execute_transfer_request", &approved_id)
+        .expect("Execution succeeds once unpaused");
+}
+
+/// Test that a non-owner can act according to the roles granted to it, that a
+/// proposer without the approver role is not counted as a supporter, and
+/// that revoked roles are denied.
+#[test]
+fn test_roles() {
+    let (mut chain, dao, _) = initialize();
+    deposit(&mut chain, dao, Amount::from_ccd(10));
+    for role in [Role::Proposer, Role::Executor] {
+        pass_proposal(&mut chain, dao, &GovernanceAction::GrantRole {
+            address: DAVE_ADDR,
+            role,
+        });
+    }
+
+    let request_id = submit_request(&mut chain, dao, DAVE_ADDR).expect("Proposer can submit");
+    let request: TransferRequest =
+        invoke(&mut chain, dao, ALICE_ADDR, "view_transfer_request", &request_id)
+            .expect("View succeeds")
+            .parse_return_value()
+            .expect("Deserialize `TransferRequest`");
+    assert!(request.supporters.is_empty());
+
+    let update = invoke(&mut chain, dao, DAVE_ADDR, "support_transfer_request", &request_id)
+        .expect_err("Dave is not an approver");
+    let error: Error = update.parse_return_value().expect("Deserialize `Error`");
+    assert_eq!(error, Error::NotOwner);
+
+    for supporter in [ALICE_ADDR, BOB_ADDR, CHARLIE_ADDR] {
+        invoke(&mut chain, dao, supporter, "support_transfer_request", &request_id)
+            .expect("Owner can support a request");
+    }
+    invoke(&mut chain, dao, DAVE_ADDR, "execute_transfer_request", &request_id)
+        .expect("Executor can execute");
+
+    pass_proposal(&mut chain, dao, &GovernanceAction::RevokeRole {
+        address: DAVE_ADDR,
+        role:    Role::Executor,
+    });
+    let request_id = submit_request(&mut chain, dao, ALICE_ADDR).expect("Submit succeeds");
+    for supporter in [BOB_ADDR, CHARLIE_ADDR] {
+        invoke(&mut chain, dao, supporter, "support_transfer_request", &request_id)
+            .expect("Owner can support a request");
+    }
+    let update = invoke(&mut chain, dao, DAVE_ADDR, "execute_transfer_request", &request_id)
+        .expect_err("Executor role was revoked");
+    let error: Error = update.parse_return_value().expect("Deserialize `Error`");
+    assert_eq!(error, Error::NotOwner);
+}
+
+/// Test that a request for the same payment as an active request is rejected,
+/// while a different memo or expiry makes it a different payment.
+#[test]
+fn test_duplicate_request() {
+    let (mut chain, dao, _) = initialize();
+
+    submit_request(&mut chain, dao, ALICE_ADDR).expect("Submit succeeds");
+    let update = submit_request(&mut chain, dao, BOB_ADDR).expect_err("Same payment");
+    let error: Error = update.parse_return_value().expect("Deserialize `Error`");
+    assert_eq!(error, Error::RequestAlreadyExists);
+
+    submit(&mut chain, dao, BOB_ADDR, &SubmitParams {
+        memo: Some("rent".to_string()),
+        ..payment()
+    })
+    .expect("Different memo is another payment");
+    submit(&mut chain, dao, BOB_ADDR, &SubmitParams {
+        expiry: Some(Timestamp::from_timestamp_millis(60 * 60 * 1000)),
+        ..payment()
+    })
+    .expect("Different expiry is another payment");
+}
+
+/// Test that a delegate supports on behalf of the owner, counted once, and
+/// acts for a single owner until the delegation is revoked.
+#[test]
+fn test_delegation() {
+    let (mut chain, dao, _) = initialize();
+    delegate(&mut chain, dao, ALICE_ADDR, DAVE, 60 * 60 * 1000).expect("Owner can delegate");
+
+    let request_id = submit_request(&mut chain, dao, BOB_ADDR).expect("Submit succeeds");
+    invoke(&mut chain, dao, DAVE_ADDR, "support_transfer_request", &request_id)
+        .expect("Delegate can support");
+    let request: TransferRequest =
+        invoke(&mut chain, dao, ALICE_ADDR, "view_transfer_request", &request_id)
+            .expect("View succeeds")
+            .parse_return_value()
+            .expect("Deserialize `TransferRequest`");
+    assert_eq!(request.supporters, [ALICE_ADDR, BOB_ADDR].into_iter().collect());
+    let update = invoke(&mut chain, dao, ALICE_ADDR, "support_transfer_request", &request_id)
+        .expect_err("Delegate already supported for the owner");
+    let error: Error = update.parse_return_value().expect("Deserialize `Error`");
+    assert_eq!(error, Error::RequestAlreadySupported);
+
+    let request_id = submit(&mut chain, dao, BOB_ADDR, &SubmitParams {
+        memo: Some("second".to_string()),
+        ..payment()
+    })
+    .expect("Submit succeeds");
+    invoke(&mut chain, dao, ALICE_ADDR, "support_transfer_request", &request_id)
+        .expect("Owner can support");
+    let update = invoke(&mut chain, dao, DAVE_ADDR, "support_transfer_request", &request_id)
+        .expect_err("Owner already supported");
+    let error: Error = update.parse_return_value().expect("Deserialize `Error`");
+    assert_eq!(error, Error::RequestAlreadySupported);
+
+    let error = delegate(&mut chain, dao, BOB_ADDR, DAVE, 60 * 60 * 1000)
+        .expect_err("Dave already acts for Alice");
+    assert_eq!(error, Error::InvalidDelegation);
+    for owner in [ALICE, BOB] {
+        let error = delegate(&mut chain, dao, ALICE_ADDR, owner, 60 * 60 * 1000)
+            .expect_err("Owners approve on their own");
+        assert_eq!(error, Error::InvalidDelegation);
+    }
+
+    invoke(&mut chain, dao, ALICE_ADDR, "revoke_delegation", &())
+        .expect("Owner can revoke a delegation");
+    let update = invoke(&mut chain, dao, DAVE_ADDR, "not_support_transfer_request", &request_id)
+        .expect_err("Delegation was revoked");
+    let error: Error = update.parse_return_value().expect("Deserialize `Error`");
+    assert_eq!(error, Error::NotOwner);
+    let update = invoke(&mut chain, dao, ALICE_ADDR, "revoke_delegation", &())
+        .expect_err("No delegation left");
+    let error: Error = update.parse_return_value().expect("Deserialize `Error`");
+    assert_eq!(error, Error::DelegationNotFound);
+}
+
+/// Test that a delegation has no effect once it expires, freeing the delegate
+/// for another owner.
+#[test]
+fn test_delegation_expiry() {
+    let (mut chain, dao, _) = initialize();
+    delegate(&mut chain, dao, ALICE_ADDR, DAVE, 10_000).expect("Owner can delegate");
+    let request_id = submit_request(&mut chain, dao, BOB_ADDR).expect("Submit succeeds");
+
+    chain.tick_block_time(Duration::from_seconds(10)).expect("Block time does not overflow");
+    let update = invoke(&mut chain, dao, DAVE_ADDR, "support_transfer_request", &request_id)
+        .expect_err("Delegation has expired");
+    let error: Error = update.parse_return_value().expect("Deserialize `Error`");
+    assert_eq!(error, Error::NotOwner);
+
+    delegate(&mut chain, dao, BOB_ADDR, DAVE, 20_000)
+        .expect("Expired delegation frees the delegate");
+}
+
+/// Test that the threshold asked for by a request is clamped between the
+/// threshold of the wallet and the number of owners.
+#[test]
+fn test_request_threshold_clamped() {
+    let (mut chain, dao, _) = initialize();
+
+    for (requested, memo) in [(1, "below"), (5, "above")] {
+        let request_id = submit(&mut chain, dao, ALICE_ADDR, &SubmitParams {
+            memo: Some(memo.to_string()),
+            threshold: Some(requested),
+            ..payment()
+        })
+        .expect("Submit succeeds");
+        assert_eq!(view_request(&mut chain, dao, request_id).threshold, 3);
+    }
+}
+
+/// Test that a request asking for more supporters than the threshold of the
+/// wallet needs all of them to be executed.
+#[test]
+fn test_request_threshold_above_wallet_threshold() {
+    let (mut chain, dao, _) = initialize();
+    deposit(&mut chain, dao, Amount::from_ccd(10));
+    set_threshold(&mut chain, dao, 2);
+
+    let request_id = submit(&mut chain, dao, ALICE_ADDR, &SubmitParams {
+        threshold: Some(3),
+        ..payment()
+    })
+    .expect("Submit succeeds");
+    invoke(&mut chain, dao, BOB_ADDR, "support_transfer_request", &request_id)
+        .expect("Owner can support a request");
+    let update = invoke(&mut chain, dao, ALICE_ADDR, "execute_transfer_request", &request_id)
+        .expect_err("Request asks for three supporters");
+    let error: Error = update.parse_return_value().expect("Deserialize `Error`");
+    assert_eq!(error, Error::RequestNotSupportedByAllOwners);
+
+    invoke(&mut chain, dao, CHARLIE_ADDR, "support_transfer_request", &request_id)
+        .expect("Owner can support a request");
+    invoke(&mut chain, dao, ALICE_ADDR, "execute_transfer_request", &request_id)
+        .expect("Execution succeeds with three supporters");
+}
+
+/// Test that a request to a contract invokes its entrypoint with the amount.
+#[test]
+fn test_contract_receiver() {
+    let (mut chain, dao, wallet) = initialize();
+    deposit(&mut chain, dao, Amount::from_ccd(10));
+
+    let request_id = submit_and_support(&mut chain, dao, Receiver::Contract(
+        wallet,
+        OwnedEntrypointName::new_unchecked("deposit".to_string()),
+    ));
+    invoke(&mut chain, dao, ALICE_ADDR, "execute_transfer_request", &request_id)
+        .expect("Execution invokes the receiving contract");
+    assert_eq!(chain.contract_balance(dao), Some(Amount::from_ccd(9)));
+    assert_eq!(chain.contract_balance(wallet), Some(Amount::from_ccd(1)));
+}
+
+/// Test that a failing invocation of the receiving contract rejects the
+/// execution, which leaves the request pending.
+#[test]
+fn test_contract_receiver_fails() {
+    let (mut chain, dao, wallet) = initialize();
+    deposit(&mut chain, dao, Amount::from_ccd(10));
+
+    let request_id = submit_and_support(&mut chain, dao, Receiver::Contract(
+        wallet,
+        OwnedEntrypointName::new_unchecked("no_such_entrypoint".to_string()),
+    ));
+    let update = invoke(&mut chain, dao, ALICE_ADDR, "execute_transfer_request", &request_id)
+        .expect_err("Receiving contract has no such entrypoint");
+    let error: Error = update.parse_return_value().expect("Deserialize `Error`");
+    assert_eq!(error, Error::InvokeContractError);
+    assert_eq!(chain.contract_balance(dao), Some(Amount::from_ccd(10)));
+    assert_eq!(view_status(&mut chain, dao, request_id), RequestStatus::Approved);
+}
+
+/// Test that only the most recent transfers are retained in the history.
+#[test]
+fn test_history_retention() {
+    let (mut chain, dao, _) = initialize_with(|params| params.history_retention = Some(1));
+    deposit(&mut chain, dao, Amount::from_ccd(10));
+
+    let mut request_ids = Vec::new();
+    for memo in ["first", "second"] {
+        let request_id = submit(&mut chain, dao, ALICE_ADDR, &SubmitParams {
+            memo: Some(memo.to_string()),
+            ..payment()
+        })
+        .expect("Submit succeeds");
+        for supporter in [BOB_ADDR, CHARLIE_ADDR] {
+            invoke(&mut chain, dao, supporter, "support_transfer_request", &request_id)
+                .expect("Owner can support a request");
+        }
+        invoke(&mut chain, dao, ALICE_ADDR, "execute_transfer_request", &request_id)
+            .expect("Execution succeeds at the threshold");
+        request_ids.push(request_id);
+    }
+
+    let history = view_history(&mut chain, dao);
+    assert_eq!(history.total, 1);
+    let retained: Vec<_> = history.entries.iter().map(|entry| entry.request_id).collect();
+    assert_eq!(retained, [Some(request_ids[1])]);
+}
+
+/// Test that the history is paged with `skip` and `limit`, and that a page
+/// holds at most `MAX_HISTORY_PAGE_SIZE` entries.
+#[test]
+fn test_history_paging() {
+    let (mut chain, dao, _) = initialize();
+    deposit(&mut chain, dao, Amount::from_ccd(10));
+    let params = QuickTransferParams {
+        transfer_amount: Amount::from_micro_ccd(1),
+        target_account:  CHARLIE,
+    };
+    for _ in 0..=MAX_HISTORY_PAGE_SIZE {
+        invoke(&mut chain, dao, ALICE_ADDR, "quick_transfer", &params)
+            .expect("Transfer within the daily limit succeeds");
+    }
+    let total = u64::from(MAX_HISTORY_PAGE_SIZE) + 1;
+
+    let page = view_history_page(&mut chain, dao, 0, MAX_HISTORY_PAGE_SIZE + 10);
+    assert_eq!(page.total, total);
+    assert_eq!(page.entries.len(), MAX_HISTORY_PAGE_SIZE as usize);
+
+    let page = view_history_page(&mut chain, dao, total - 1, 10);
+    assert_eq!(page.entries.len(), 1);
+
+    let page = view_history_page(&mut chain, dao, total + 5, 10);
+    assert_eq!(page.total, total);
+    assert!(page.entries.is_empty());
+}
+
+/// Test that memos longer than `MAX_MEMO_LENGTH` bytes are rejected, and that
+/// a memo is returned with the request and logged when it is submitted.
+#[test]
+fn test_memo() {
+    let (mut chain, dao, _) = initialize();
+
+    let update = submit(&mut chain, dao, ALICE_ADDR, &SubmitParams {
+        memo: Some("a".repeat(MAX_MEMO_LENGTH + 1)),
+        ..payment()
+    })
+    .expect_err("Memo is too long");
     let error: Error = update.parse_return_value().expect("Deserialize `Error`");
-    assert_eq!(error, Error::YourError);
+    assert_eq!(error, Error::MemoTooLong);
+
+    let memo = Some("a".repeat(MAX_MEMO_LENGTH));
+    let params = SubmitParams {
+        memo: memo.clone(),
+        ..payment()
+    };
+    let update = invoke(&mut chain, dao, ALICE_ADDR, "submit_transfer_request", &params)
+        .expect("Memo of the maximum length is accepted");
+    let request_id: TransferRequestId =
+        update.parse_return_value().expect("Deserialize `TransferRequestId`");
+
+    let logged_memo = update
+        .events()
+        .flat_map(|(_, events)| events.iter())
+        .find_map(|event| match event.parse().expect("Deserialize `Event`") {
+            Event::Submitted(submitted) => Some(submitted.memo),
+            _ => None,
+        })
+        .expect("Submission is logged");
+    assert_eq!(logged_memo, memo);
+    assert_eq!(view_request(&mut chain, dao, request_id).memo, memo);
+}
+
+/// Helper method for invoking an entrypoint of the wallet.
+fn invoke<P: Serial>(
+    chain: &mut Chain,
+    wallet: ContractAddress,
+    sender: Address,
+    entrypoint: &str,
+    parameter: &P,
+) -> Result<ContractInvokeSuccess, ContractInvokeError> {
+    chain.contract_update(SIGNER, ALICE, sender, Energy::from(100_000), UpdateContractPayload {
+        address:      wallet,
+        amount:       Amount::zero(),
+        receive_name: OwnedReceiveName::new_unchecked(format!("multisig_wallet.{}", entrypoint)),
+        message:      OwnedParameter::from_serial(parameter).expect("Parameter within size bounds"),
+    })
+}
+
+/// Helper method for viewing a request as `ALICE`.
+fn view_request(
+    chain: &mut Chain,
+    wallet: ContractAddress,
+    request_id: TransferRequestId,
+) -> TransferRequest {
+    invoke(chain, wallet, ALICE_ADDR, "view_transfer_request", &request_id)
+        .expect("View succeeds")
+        .parse_return_value()
+        .expect("Deserialize `TransferRequest`")
+}
+
+/// Helper method for viewing the status of a request as `ALICE`.
+fn view_status(
+    chain: &mut Chain,
+    wallet: ContractAddress,
+    request_id: TransferRequestId,
+) -> RequestStatus {
+    view_request(chain, wallet, request_id).status
+}
+
+/// Helper method for submitting a governance proposal as `ALICE`.
+fn propose(
+    chain: &mut Chain,
+    wallet: ContractAddress,
+    action: &GovernanceAction,
+) -> GovernanceProposalId {
+    invoke(chain, wallet, ALICE_ADDR, "submit_governance_proposal", action)
+        .expect("Owner can submit a proposal")
+        .parse_return_value()
+        .expect("Deserialize `GovernanceProposalId`")
+}
+
+/// Helper method for supporting a governance proposal by the given owners.
+fn support_proposal(
+    chain: &mut Chain,
+    wallet: ContractAddress,
+    proposal_id: GovernanceProposalId,
+    supporters: &[Address],
+) {
+    for supporter in supporters {
+        invoke(chain, wallet, *supporter, "support_governance_proposal", &proposal_id)
+            .expect("Owner can support a proposal");
+    }
+}
+
+/// Helper method for passing a governance proposal in a wallet owned by
+/// `ALICE`, `BOB` and `CHARLIE`.
+fn pass_proposal(chain: &mut Chain, wallet: ContractAddress, action: &GovernanceAction) {
+    let proposal_id = propose(chain, wallet, action);
+    support_proposal(chain, wallet, proposal_id, &[BOB_ADDR, CHARLIE_ADDR]);
+    invoke(chain, wallet, ALICE_ADDR, "execute_governance_proposal", &proposal_id)
+        .expect("Approved proposal can be executed");
+}
+
+/// Helper method for changing the threshold of a wallet owned by `ALICE`,
+/// `BOB` and `CHARLIE`.
+fn set_threshold(chain: &mut Chain, wallet: ContractAddress, threshold: u32) {
+    let proposal_id: GovernanceProposalId =
+        invoke(chain, wallet, ALICE_ADDR, "propose_threshold_change", &threshold)
+            .expect("Owner can propose a threshold")
+            .parse_return_value()
+            .expect("Deserialize `GovernanceProposalId`");
+    support_proposal(chain, wallet, proposal_id, &[BOB_ADDR, CHARLIE_ADDR]);
+    invoke(chain, wallet, ALICE_ADDR, "execute_threshold_change", &proposal_id)
+        .expect("Threshold change succeeds");
+}
+
+/// The ed25519 key pair each test account uses for off-chain signatures.
+fn keypair(owner: Address) -> Keypair {
+    let seed = match owner {
+        Address::Account(AccountAddress(bytes)) => bytes,
+        Address::Contract(_) => panic!("Only accounts have keys"),
+    };
+    let secret = SecretKey::from_bytes(&seed).expect("Valid secret key");
+    let public = PublicKey::from(&secret);
+    Keypair {
+        secret,
+        public,
+    }
+}
+
+/// Helper method for registering the keys of the given owners.
+fn register_owner_keys(chain: &mut Chain, wallet: ContractAddress, owners: &[Address]) {
+    for owner in owners {
+        let public_key = PublicKeyEd25519(keypair(*owner).public.to_bytes());
+        invoke(chain, wallet, *owner, "register_owner_key", &public_key)
+            .expect("Owner can register a key");
+    }
+}
+
+/// Sign an approval of `request` as `owner`.
+fn sign_approval(
+    owner: Address,
+    wallet: ContractAddress,
+    nonce: u64,
+    request: &SubmitParams,
+) -> OwnerSignature {
+    let message = to_bytes(&SignedApprovalMessage {
+        chain_context: HashSha2256([0u8; 32]),
+        contract_address: wallet,
+        nonce,
+        request: request.clone(),
+    });
+    OwnerSignature {
+        owner,
+        signature: SignatureEd25519(keypair(owner).sign(&message).to_bytes()),
+    }
+}
+
+/// Sign approvals of [`payment`] as each of the given owners.
+fn sign_approvals(wallet: ContractAddress, nonce: u64, owners: &[Address]) -> Vec<OwnerSignature> {
+    owners.iter().map(|owner| sign_approval(*owner, wallet, nonce, &payment())).collect()
+}
+
+/// Helper method for requesting a refund as `DAVE`.
+fn request_refund(
+    chain: &mut Chain,
+    wallet: ContractAddress,
+    amount: Amount,
+) -> Result<TransferRequestId, Error> {
+    invoke(chain, wallet, DAVE_ADDR, "request_refund", &amount)
+        .map(|update| update.parse_return_value().expect("Deserialize `TransferRequestId`"))
+        .map_err(|update| update.parse_return_value().expect("Deserialize `Error`"))
 }
 
-/// Helper method for initializing the contract.
+/// Helper method for submitting [`payment`] with the given signatures.
+fn submit_signed(
+    chain: &mut Chain,
+    wallet: ContractAddress,
+    sender: Address,
+    nonce: u64,
+    signatures: Vec<OwnerSignature>,
+) -> Result<TransferRequestId, Error> {
+    let params = SignedSubmitParams {
+        request: payment(),
+        nonce,
+        signatures,
+    };
+    match invoke(chain, wallet, sender, "submit_signed_transfer_request", &params) {
+        Ok(update) => Ok(update.parse_return_value().expect("Deserialize `TransferRequestId`")),
+        Err(update) => Err(update.parse_return_value().expect("Deserialize `Error`")),
+    }
+}
+
+/// Replace an account by one with generated keys, so it can sign messages.
+fn create_account_with_keys(chain: &mut Chain, account: AccountAddress) -> AccountKeys {
+    let keys = AccountKeys::singleton(&mut StdRng::seed_from_u64(0));
+    let balance = AccountBalance {
+        total:  ACC_INITIAL_BALANCE,
+        staked: Amount::zero(),
+        locked: Amount::zero(),
+    };
+    let access_structure: AccountAccessStructure = (&keys).into();
+    chain.create_account(Account::new_with_keys(account, balance, access_structure));
+    keys
+}
+
+/// A permit message valid for an hour, invoking `entry_point` with a request
+/// id.
+fn permit_message(
+    wallet: ContractAddress,
+    nonce: u64,
+    entry_point: &str,
+    request_id: TransferRequestId,
+) -> PermitMessage {
+    PermitMessage {
+        contract_address: wallet,
+        nonce,
+        timestamp: Timestamp::from_timestamp_millis(60 * 60 * 1000),
+        entry_point: OwnedEntrypointName::new_unchecked(entry_point.to_string()),
+        payload: to_bytes(&request_id),
+    }
+}
+
+/// Helper method for signing a permit as `ALICE` and relaying it as `DAVE`.
+fn permit(
+    chain: &mut Chain,
+    wallet: ContractAddress,
+    keys: &AccountKeys,
+    message: PermitMessage,
+) -> Result<(), Error> {
+    let mut param = PermitParam {
+        signature: AccountSignatures {
+            sigs: Default::default(),
+        },
+        signer: ALICE,
+        message,
+    };
+    let message_hash: [u8; 32] = invoke(chain, wallet, DAVE_ADDR, "viewMessageHash", &param)
+        .expect("View succeeds")
+        .parse_return_value()
+        .expect("Deserialize message hash");
+    param.signature = keys.sign_message(&message_hash);
+
+    invoke(chain, wallet, DAVE_ADDR, "permit", &param)
+        .map(|_| ())
+        .map_err(|update| update.parse_return_value().expect("Deserialize `Error`"))
+}
+
+/// Helper method for submitting a recurring payment of 1 CCD to `CHARLIE` as
+/// `ALICE`.
+fn submit_recurring(
+    chain: &mut Chain,
+    wallet: ContractAddress,
+    schedule: RecurringSchedule,
+) -> Result<ContractInvokeSuccess, ContractInvokeError> {
+    let params = RecurringSubmitParams {
+        transfer_amount: Amount::from_ccd(1),
+        receiver: Receiver::Account(CHARLIE),
+        schedule,
+        memo: None,
+        expiry: None,
+    };
+    invoke(chain, wallet, ALICE_ADDR, "submit_recurring_transfer_request", &params)
+}
+
+/// Helper method for submitting a recurring payment and executing it with the
+/// support of every owner, which activates the schedule.
+fn activate_schedule(
+    chain: &mut Chain,
+    wallet: ContractAddress,
+    schedule: RecurringSchedule,
+) -> TransferRequestId {
+    let schedule_id: TransferRequestId = submit_recurring(chain, wallet, schedule)
+        .expect("Submit succeeds")
+        .parse_return_value()
+        .expect("Deserialize `TransferRequestId`");
+    for supporter in [BOB_ADDR, CHARLIE_ADDR] {
+        invoke(chain, wallet, supporter, "support_transfer_request", &schedule_id)
+            .expect("Owner can support a request");
+    }
+    invoke(chain, wallet, ALICE_ADDR, "execute_transfer_request", &schedule_id)
+        .expect("Execution activates the schedule");
+    schedule_id
+}
+
+/// Helper method for viewing the first page of the history.
+fn view_history(chain: &mut Chain, wallet: ContractAddress) -> ViewHistoryResponse {
+    view_history_page(chain, wallet, 0, 10)
+}
+
+/// Helper method for viewing a page of the history.
+fn view_history_page(
+    chain: &mut Chain,
+    wallet: ContractAddress,
+    skip: u64,
+    limit: u32,
+) -> ViewHistoryResponse {
+    invoke(chain, wallet, ALICE_ADDR, "view_history", &ViewHistoryParams {
+        skip,
+        limit,
+    })
+    .expect("View succeeds")
+    .parse_return_value()
+    .expect("Deserialize `ViewHistoryResponse`")
+}
+
+/// Helper method for delegating the approval power of an owner until the given
+/// block time.
+fn delegate(
+    chain: &mut Chain,
+    wallet: ContractAddress,
+    owner: Address,
+    delegate: AccountAddress,
+    expiry_millis: u64,
+) -> Result<(), Error> {
+    let delegation = Delegation {
+        delegate,
+        expiry: Timestamp::from_timestamp_millis(expiry_millis),
+    };
+    invoke(chain, wallet, owner, "delegate_to", &delegation)
+        .map(|_| ())
+        .map_err(|update| update.parse_return_value().expect("Deserialize `Error`"))
+}
+
+/// Helper method for depositing CCD from `ALICE` into the wallet.
+fn deposit(chain: &mut Chain, wallet: ContractAddress, amount: Amount) {
+    deposit_from(chain, wallet, ALICE, amount)
+}
+
+/// Helper method for depositing CCD from an account into the wallet.
+fn deposit_from(
+    chain: &mut Chain,
+    wallet: ContractAddress,
+    depositor: AccountAddress,
+    amount: Amount,
+) {
+    let sender = Address::Account(depositor);
+    chain
+        .contract_update(SIGNER, depositor, sender, Energy::from(100_000), UpdateContractPayload {
+            address:      wallet,
+            amount,
+            receive_name: OwnedReceiveName::new_unchecked("multisig_wallet.deposit".to_string()),
+            message:      OwnedParameter::empty(),
+        })
+        .expect("Deposit succeeds");
+}
+
+/// The parameter for a transfer request of 1 CCD to `CHARLIE`.
+fn payment() -> SubmitParams {
+    SubmitParams {
+        transfer_amount: Amount::from_ccd(1),
+        receiver:        Receiver::Account(CHARLIE),
+        memo:            None,
+        threshold:       None,
+        expiry:          None,
+    }
+}
+
+/// Helper method for submitting a transfer request of 1 CCD to `CHARLIE`.
+fn submit_request(
+    chain: &mut Chain,
+    wallet: ContractAddress,
+    sender: Address,
+) -> Result<TransferRequestId, ContractInvokeError> {
+    submit(chain, wallet, sender, &payment())
+}
+
+/// Helper method for submitting a transfer request of 1 CCD to `receiver`,
+/// supported by `ALICE`, `BOB` and `CHARLIE`.
+fn submit_and_support(
+    chain: &mut Chain,
+    wallet: ContractAddress,
+    receiver: Receiver,
+) -> TransferRequestId {
+    let request_id = submit(chain, wallet, ALICE_ADDR, &SubmitParams {
+        receiver,
+        ..payment()
+    })
+    .expect("Submit succeeds");
+    for supporter in [BOB_ADDR, CHARLIE_ADDR] {
+        invoke(chain, wallet, supporter, "support_transfer_request", &request_id)
+            .expect("Owner can support a request");
+    }
+    request_id
+}
+
+/// Helper method for submitting a transfer request.
+fn submit(
+    chain: &mut Chain,
+    wallet: ContractAddress,
+    sender: Address,
+    params: &SubmitParams,
+) -> Result<TransferRequestId, ContractInvokeError> {
+    let update = invoke(chain, wallet, sender, "submit_transfer_request", params)?;
+    Ok(update.parse_return_value().expect("Deserialize `TransferRequestId`"))
+}
+
+/// Helper method for initializing a wallet with the given owners, after
+/// adjusting the default parameters with `configure`.
+fn init_wallet(
+    chain: &mut Chain,
+    module_reference: ModuleReference,
+    owners: BTreeSet<Address>,
+    configure: &impl Fn(&mut InitParams),
+) -> ContractAddress {
+    let mut params = InitParams {
+        owners,
+        execution_delay: Duration::from_millis(0),
+        daily_limit: Amount::from_ccd(100),
+        chain_context: HashSha2256([0u8; 32]),
+        history_retention: None,
+        max_pending_requests: 10,
+    };
+    configure(&mut params);
+    chain
+        .contract_init(SIGNER, ALICE, Energy::from(100_000), InitContractPayload {
+            amount:    Amount::zero(),
+            mod_ref:   module_reference,
+            init_name: OwnedContractName::new_unchecked("init_multisig_wallet".to_string()),
+            param:     OwnedParameter::from_serial(&params).expect("Parameter within size bounds"),
+        })
+        .expect("Initializing contract")
+        .contract_address
+}
+
+/// Helper method for initializing the contracts.
 ///
 /// Does the following:
 ///  - Creates the [`Chain`]
-///  - Creates one account, `Alice` with `10_000` CCD as the initial balance.
-///  - Initializes the contract.
-///  - Returns the [`Chain`] and the [`ContractInitSuccess`]
-fn initialize() -> (Chain, ContractInitSuccess) {
+///  - Creates the accounts `Alice`, `Bob`, `Charlie` and `Dave` with `10_000`
+///    CCD as the initial balance.
+///  - Initializes a wallet owned by the three accounts, acting as a DAO.
+///  - Initializes a wallet owned by `Alice`, `Bob` and the DAO.
+///  - Returns the [`Chain`], the DAO and the wallet.
+fn initialize() -> (Chain, ContractAddress, ContractAddress) {
+    initialize_with(|_| {})
+}
+
+/// Helper method for initializing the contracts like [`initialize`], with
+/// `configure` applied to the parameters of both wallets.
+fn initialize_with(
+    configure: impl Fn(&mut InitParams),
+) -> (Chain, ContractAddress, ContractAddress) {
     // Initialize the test chain.
     let mut chain = Chain::new();
 
-    // Create the test account.
+    // Create the test accounts.
     chain.create_account(Account::new(ALICE, ACC_INITIAL_BALANCE));
+    chain.create_account(Account::new(BOB, ACC_INITIAL_BALANCE));
+    chain.create_account(Account::new(CHARLIE, ACC_INITIAL_BALANCE));
+    chain.create_account(Account::new(DAVE, ACC_INITIAL_BALANCE));
 
     // Load the module.
     let module = module_load_v1("./concordium-out/module.wasm.v1").expect("Module exists at path");
     // Deploy the module.
     let deployment = chain.module_deploy_v1(SIGNER, ALICE, module).expect("Deploy valid module");
 
-    // Initialize the contract.
-    let init = chain
-        .contract_init(SIGNER, ALICE, Energy::from(10_000), InitContractPayload {
-            amount:    Amount::zero(),
-            mod_ref:   deployment.module_reference,
-            init_name: OwnedContractName::new_unchecked("init_multi_sig_contract".to_string()),
-            param:     OwnedParameter::empty(),
-        })
-        .expect("Initializing contract");
+    // Initialize the contracts.
+    let dao = init_wallet(
+        &mut chain,
+        deployment.module_reference,
+        [ALICE_ADDR, BOB_ADDR, CHARLIE_ADDR].into_iter().collect(),
+        &configure,
+    );
+    let wallet = init_wallet(
+        &mut chain,
+        deployment.module_reference,
+        [ALICE_ADDR, BOB_ADDR, Address::Contract(dao)].into_iter().collect(),
+        &configure,
+    );
 
-    (chain, init)
+    (chain, dao, wallet)
 }